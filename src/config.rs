@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::files::TransformRule;
+
+const CONFIG_FILE_NAME: &str = ".prompt.yaml";
+
+/// Project configuration, read from a `.prompt.yaml` at the root of the first given path.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub transforms: Vec<TransformRule>,
+}
+
+impl Config {
+    /// Loads the config for the project at `path`, defaulting if there is no config file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let root = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let config_path = root.join(CONFIG_FILE_NAME);
+        if !config_path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&config_path)?;
+        serde_norway::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", config_path.display()))
+    }
+}
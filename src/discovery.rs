@@ -5,26 +5,89 @@ use std::sync::Arc;
 use anyhow::Result;
 use dashmap::DashSet;
 use home::home_dir;
-use ignore::gitignore::Gitignore;
+use ignore::gitignore::{Gitignore, GitignoreBuilder, Glob, gitconfig_excludes_path};
+use ignore::types::{FileTypeDef, Types, TypesBuilder};
 use ignore::{Match as IgnoreMatch, WalkBuilder, WalkState};
 use tracing::warn;
 
+use self::classify::Classifier;
+pub use self::classify::{Classification, FileClass};
 use crate::files::strip_dot_prefix;
+use crate::tree::FileCount;
+
+mod classify;
 
 const PROMPT_HOME_OVERRIDE_ENV: &str = "PROMPT_HOME_DIR";
+/// Files counted under a pruned directory before giving up, so huge trees stay cheap to skip.
+const PRUNED_COUNT_LIMIT: usize = 10_000;
 
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub struct DiscoveredFile {
     pub path: PathBuf,
     pub excluded: bool,
+    /// Set when `path` is an excluded directory that was pruned from the walk, to the number of
+    /// files that would otherwise have been discovered under it.
+    pub pruned: Option<FileCount>,
+    /// Set for files classified as generated, vendored, minified or Git LFS pointers.
+    pub class: Option<FileClass>,
+}
+
+/// Options controlling which files are discovered and which are marked excluded.
+#[derive(Debug, Default, Clone)]
+pub struct DiscoverOptions {
+    pub exclude: Vec<glob::Pattern>,
+    pub no_gitignore: bool,
+    /// File type names (as in `ripgrep --type`) that files must match to be discovered.
+    pub types: Vec<String>,
+    /// File type names that discovered files must not match.
+    pub types_not: Vec<String>,
+    /// Extra file type definitions in ripgrep's `name:glob` format.
+    pub type_add: Vec<String>,
+    /// Sensitive files allowed with `--allow-sensitive`, which are classified by their content
+    /// like other files. Other sensitive files are never read.
+    pub allow_sensitive: Vec<glob::Pattern>,
+}
+
+/// Returns all known file type definitions, including any added with `--type-add`.
+pub fn type_definitions(type_add: &[String]) -> Result<Vec<FileTypeDef>> {
+    Ok(types_builder(type_add)?.definitions())
+}
+
+fn types_builder(type_add: &[String]) -> Result<TypesBuilder> {
+    let mut builder = TypesBuilder::new();
+    builder.add_defaults();
+    for def in type_add {
+        builder.add_def(def)?;
+    }
+    Ok(builder)
+}
+
+fn build_types(options: &DiscoverOptions) -> Result<Types> {
+    let mut builder = types_builder(&options.type_add)?;
+    for name in &options.types {
+        builder.select(name);
+    }
+    for name in &options.types_not {
+        builder.negate(name);
+    }
+    Ok(builder.build()?)
 }
 
 /// Returns a sorted [`Vec`] of [`DiscoveredFile`]s
 pub fn discover(
     path: PathBuf,
     extra_paths: Vec<PathBuf>,
-    exclude: Vec<glob::Pattern>,
-    no_gitignore: bool,
+    options: &DiscoverOptions,
+) -> Result<Vec<DiscoveredFile>> {
+    discover_pruning(path, extra_paths, options, true)
+}
+
+/// Discovers files as [`discover`] does, pruning excluded directories only if `prune` is set.
+fn discover_pruning(
+    path: PathBuf,
+    extra_paths: Vec<PathBuf>,
+    options: &DiscoverOptions,
+    prune: bool,
 ) -> Result<Vec<DiscoveredFile>> {
     // Helper function to create error message for non-existent paths
     let path_not_found_error = |path: &PathBuf| {
@@ -52,42 +115,39 @@ pub fn discover(
         match_bases.push(extra_path.clone());
     }
 
-    // Include canonicalized bases to cover situations where walker entries are absolute
-    // while the user supplied relative paths (or the other way around).
-    let mut canonical_bases = Vec::with_capacity(match_bases.len());
-    let mut promptignore_roots = Vec::with_capacity(match_bases.len());
-    for base in &match_bases {
-        if let Ok(canonical) = std::fs::canonicalize(base) {
-            if let Some(root) = promptignore_root(&canonical) {
-                promptignore_roots.push(root);
-            }
-            canonical_bases.push(canonical);
-        }
-    }
-    match_bases.extend(canonical_bases);
+    let absolute_bases = match_bases
+        .iter()
+        .filter_map(|base| Some((base.clone(), std::fs::canonicalize(base).ok()?)))
+        .collect::<Vec<_>>();
+    let (match_bases, promptignore_roots) = resolve_bases(match_bases);
     let match_bases = Arc::new(match_bases);
+    let absolute_bases = Arc::new(absolute_bases);
     let promptignore_roots = Arc::new(promptignore_roots);
-    walker.hidden(false);
     // use thread heuristic from  https://github.com/BurntSushi/ripgrep/issues/2854
     walker.threads(
         std::thread::available_parallelism()
             .map_or(1, |n| n.get())
             .min(12),
     );
-    if no_gitignore {
-        walker.git_ignore(false);
-        walker.git_global(false);
-        walker.git_exclude(false);
-    }
+    let types = build_types(options)?;
+    configure_walker(&mut walker, options, types.clone());
     let walker = walker.build_parallel();
 
     // TODO: use channel to collect results and return early error
     let discovered = Arc::new(DashSet::new());
-    let exclude = Arc::new(exclude);
+    let exclude = Arc::new(options.exclude.clone());
+    let directory_exclude = Arc::new(directory_patterns(&options.exclude));
     walker.run(|| {
         let match_bases = Arc::clone(&match_bases);
+        let absolute_bases = Arc::clone(&absolute_bases);
+        let promptignore_roots = Arc::clone(&promptignore_roots);
         let exclude = Arc::clone(&exclude);
+        let directory_exclude = Arc::clone(&directory_exclude);
         let discovered = Arc::clone(&discovered);
+        let types = types.clone();
+        // each walker thread gets its own matcher so .promptignore lookups don't need locking
+        let mut promptignore = PromptignoreMatcher::new();
+        let mut classifier = Classifier::new(&options.allow_sensitive);
         Box::new(move |result| match result {
             Ok(dir_entry) => {
                 let path = dir_entry.path().to_owned();
@@ -96,19 +156,57 @@ pub fn discover(
                     if path.components().any(|c| c.as_os_str() == ".git") {
                         return WalkState::Skip;
                     }
-                    return WalkState::Continue;
+                    if dir_entry.depth() == 0 || !prune {
+                        return WalkState::Continue;
+                    }
+                    let match_path = relativize_for_match(&path, match_bases.as_slice());
+                    let excluded_by_glob = directory_exclude
+                        .iter()
+                        .any(|pattern| pattern.matches_path(&match_path));
+                    let excluded = excluded_by_glob || {
+                        let absolute_path = absolutize(&path, &absolute_bases);
+                        let root = find_root_for_path(&absolute_path, &promptignore_roots);
+                        promptignore.ignores_whole_dir(&absolute_path, root.map(|r| r.as_path()))
+                    };
+                    if !excluded {
+                        return WalkState::Continue;
+                    }
+                    let files = count_pruned_files(&path, options, &types);
+                    if files != FileCount::default() {
+                        discovered.insert(DiscoveredFile {
+                            path: strip_dot_prefix(&path).to_owned(),
+                            excluded: true,
+                            pruned: Some(files),
+                            class: None,
+                        });
+                    }
+                    return WalkState::Skip;
                 }
                 if path.is_symlink() {
                     return WalkState::Skip;
                 }
                 let match_path = relativize_for_match(&path, match_bases.as_slice());
                 let stored_path = strip_dot_prefix(&path).to_owned();
+                let absolute_path = absolutize(&path, &absolute_bases);
                 let excluded = exclude
                     .iter()
-                    .any(|pattern| pattern.matches_path(&match_path));
+                    .any(|pattern| pattern.matches_path(&match_path))
+                    || {
+                        let root = find_root_for_path(&absolute_path, &promptignore_roots);
+                        promptignore.matches(&absolute_path, root.map(|r| r.as_path()))
+                    };
+                let class = if excluded {
+                    None
+                } else {
+                    classifier
+                        .classify(&absolute_path, &match_path)
+                        .map(|classification| classification.class)
+                };
                 discovered.insert(DiscoveredFile {
                     path: stored_path,
                     excluded,
+                    pruned: None,
+                    class,
                 });
                 WalkState::Continue
             }
@@ -119,11 +217,230 @@ pub fn discover(
     });
     let discovered = Arc::try_unwrap(discovered).expect("walker should release all refs");
     let mut discovered: Vec<_> = discovered.into_iter().collect();
-    apply_promptignore(&mut discovered, &promptignore_roots);
     discovered.sort_by(|a, b| a.path.cmp(&b.path));
     Ok(discovered)
 }
 
+fn configure_walker(walker: &mut WalkBuilder, options: &DiscoverOptions, types: Types) {
+    walker.hidden(false);
+    // files filtered out by type never show up, mirroring ripgrep
+    walker.types(types);
+    if options.no_gitignore {
+        walker.git_ignore(false);
+        walker.git_global(false);
+        walker.git_exclude(false);
+    }
+}
+
+/// Returns patterns matching directories whose files would all be matched by an exclude glob,
+/// i.e. the `dir` of any `dir/**` pattern.
+fn directory_patterns(exclude: &[glob::Pattern]) -> Vec<glob::Pattern> {
+    exclude
+        .iter()
+        .filter_map(|pattern| pattern.as_str().strip_suffix("/**"))
+        .filter_map(|prefix| glob::Pattern::new(prefix).ok())
+        .collect()
+}
+
+/// Counts the files the walker would discover under a pruned `dir`, up to
+/// [`PRUNED_COUNT_LIMIT`]. Only directory entries are listed, no file is opened.
+fn count_pruned_files(dir: &Path, options: &DiscoverOptions, types: &Types) -> FileCount {
+    let mut walker = WalkBuilder::new(dir);
+    configure_walker(&mut walker, options, types.clone());
+    walker.filter_entry(|entry| entry.file_name() != ".git");
+    let mut files = walker.build().filter(|entry| {
+        entry.as_ref().is_ok_and(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+    });
+    let counted = files.by_ref().take(PRUNED_COUNT_LIMIT).count();
+    FileCount {
+        files: counted,
+        capped: files.next().is_some(),
+    }
+}
+
+/// Maps a walked path onto an absolute path, without canonicalizing every file.
+fn absolutize(path: &Path, bases: &[(PathBuf, PathBuf)]) -> PathBuf {
+    for (base, canonical) in bases {
+        if let Ok(stripped) = path.strip_prefix(base) {
+            if stripped.as_os_str().is_empty() {
+                return canonical.clone();
+            }
+            return canonical.join(stripped);
+        }
+    }
+    canonicalize_for_promptignore(path)
+}
+
+/// Returns the bases used for matching exclude globs, and the roots for finding `.promptignore`s.
+fn resolve_bases(mut match_bases: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    // Include canonicalized bases to cover situations where walker entries are absolute
+    // while the user supplied relative paths (or the other way around).
+    let mut canonical_bases = Vec::with_capacity(match_bases.len());
+    let mut promptignore_roots = Vec::with_capacity(match_bases.len());
+    for base in &match_bases {
+        if let Ok(canonical) = std::fs::canonicalize(base) {
+            if let Some(root) = promptignore_root(&canonical) {
+                promptignore_roots.push(root);
+            }
+            canonical_bases.push(canonical);
+        }
+    }
+    match_bases.extend(canonical_bases);
+    (match_bases, promptignore_roots)
+}
+
+/// Why a path would or wouldn't be part of a prompt, as far as discovery is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Explanation {
+    NotFound,
+    Directory,
+    OutsidePaths,
+    Symlink,
+    GitDirectory,
+    TypeFilter,
+    Gitignored(IgnoreRule),
+    ExcludeGlob(String),
+    Promptignored {
+        rule: IgnoreRule,
+        overrides: Vec<IgnoreRule>,
+    },
+    Discovered {
+        /// `.promptignore` whitelist rule re-including the file, and the rules it overrides.
+        whitelisted: Option<(IgnoreRule, Vec<IgnoreRule>)>,
+        /// The file's class, if it was classified as generated, vendored or the like.
+        class: Option<Classification>,
+    },
+}
+
+/// Explains the rule deciding whether `target` is discovered and excluded when discovering
+/// files under `paths`.
+pub fn explain(target: &Path, paths: &[PathBuf], options: &DiscoverOptions) -> Explanation {
+    if !target.exists() && !target.is_symlink() {
+        return Explanation::NotFound;
+    }
+    if target.is_dir() {
+        return Explanation::Directory;
+    }
+    let absolute_path = canonicalize_for_promptignore(target);
+    let (match_bases, promptignore_roots) = resolve_bases(paths.to_vec());
+    let Some(root) = find_root_for_path(&absolute_path, &promptignore_roots) else {
+        return Explanation::OutsidePaths;
+    };
+    if target.is_symlink() {
+        return Explanation::Symlink;
+    }
+    if absolute_path.components().any(|c| c.as_os_str() == ".git") {
+        return Explanation::GitDirectory;
+    }
+    if build_types(options).is_ok_and(|types| types.matched(&absolute_path, false).is_ignore()) {
+        return Explanation::TypeFilter;
+    }
+    if let Some(rule) = find_gitignore_rule(&absolute_path, root, options.no_gitignore) {
+        return Explanation::Gitignored(rule);
+    }
+
+    let match_path = relativize_for_match(target, &match_bases);
+    if let Some(pattern) = options
+        .exclude
+        .iter()
+        .find(|pattern| pattern.matches_path(&match_path))
+    {
+        return Explanation::ExcludeGlob(pattern.to_string());
+    }
+
+    let mut matcher = PromptignoreMatcher::new();
+    // a wholly ignored directory is pruned, so nothing under it can be whitelisted again
+    for dir in directory_chain_within(&absolute_path, root).iter().skip(1) {
+        if matcher.ignores_whole_dir(dir, Some(root)) {
+            let mut rules = matcher.matching_rules(dir, Some(root), true);
+            let rule = rules
+                .pop()
+                .expect("an ignored directory has a deciding rule");
+            return Explanation::Promptignored {
+                rule,
+                overrides: rules,
+            };
+        }
+    }
+    let mut rules = matcher.matching_rules(&absolute_path, Some(root), false);
+    let whitelisted = match rules.pop() {
+        Some(rule) if rule.whitelist => {
+            let overrides = rules.into_iter().filter(|rule| !rule.whitelist).collect();
+            Some((rule, overrides))
+        }
+        Some(rule) => {
+            return Explanation::Promptignored {
+                rule,
+                overrides: rules,
+            };
+        }
+        None => None,
+    };
+    Explanation::Discovered {
+        whitelisted,
+        class: Classifier::new(&options.allow_sensitive).classify(&absolute_path, &match_path),
+    }
+}
+
+/// Finds the `.gitignore` (or `.ignore`) rule that would stop the walker from reaching `path`.
+fn find_gitignore_rule(path: &Path, root: &Path, no_gitignore: bool) -> Option<IgnoreRule> {
+    let repo_root = path
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .filter(|_| !no_gitignore);
+
+    // ordered by increasing precedence, as the walker applies them
+    let mut matchers = Vec::new();
+    if let Some(repo_root) = repo_root {
+        let excludes = [
+            gitconfig_excludes_path(),
+            Some(repo_root.join(".git/info/exclude")),
+        ];
+        for excludes in excludes.into_iter().flatten() {
+            let mut builder = GitignoreBuilder::new(repo_root);
+            builder.add(excludes);
+            if let Ok(matcher) = builder.build() {
+                matchers.push(matcher);
+            }
+        }
+    }
+    let ancestors = path.ancestors().skip(1).collect::<Vec<_>>();
+    for filename in [".gitignore", ".ignore"] {
+        if filename == ".gitignore" && repo_root.is_none() {
+            continue;
+        }
+        for dir in ancestors.iter().rev() {
+            let file = dir.join(filename);
+            if file.exists() {
+                let (matcher, _) = Gitignore::new(file);
+                matchers.push(matcher);
+            }
+        }
+    }
+
+    // directories are checked top-down as the walker would, so an ignored parent is decisive
+    let candidates = directory_chain_within(path, root)
+        .into_iter()
+        .skip(1)
+        .map(|dir| (dir, true))
+        .chain(std::iter::once((path.to_path_buf(), false)));
+    for (candidate, is_dir) in candidates {
+        let rule = matchers
+            .iter()
+            .rev()
+            .filter(|matcher| candidate.starts_with(matcher.path()))
+            .find_map(|matcher| IgnoreRule::from_match(matcher.matched(&candidate, is_dir)));
+        if let Some(rule) = rule.filter(|rule| !rule.whitelist) {
+            return Some(rule);
+        }
+    }
+    None
+}
+
 fn relativize_for_match(path: &Path, bases: &[PathBuf]) -> PathBuf {
     for base in bases {
         if let Ok(stripped) = path.strip_prefix(base) {
@@ -146,17 +463,6 @@ fn promptignore_root(path: &Path) -> Option<PathBuf> {
     }
 }
 
-fn apply_promptignore(discovered: &mut [DiscoveredFile], roots: &[PathBuf]) {
-    let mut matcher = PromptignoreMatcher::new();
-    for entry in discovered {
-        let absolute_path = canonicalize_for_promptignore(&entry.path);
-        let root = find_root_for_path(&absolute_path, roots);
-        if matcher.matches(&absolute_path, root.map(|r| r.as_path())) {
-            entry.excluded = true;
-        }
-    }
-}
-
 fn find_root_for_path<'a>(path: &Path, roots: &'a [PathBuf]) -> Option<&'a PathBuf> {
     roots
         .iter()
@@ -169,26 +475,47 @@ struct PromptignoreMatcher {
     global: Option<Gitignore>,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum PromptignoreDecision {
-    None,
-    Ignore,
-    Whitelist,
+/// A pattern from an ignore file that matched a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRule {
+    pub pattern: String,
+    pub source: Option<PathBuf>,
+    pub whitelist: bool,
 }
 
-impl PromptignoreDecision {
-    fn from_match(mat: IgnoreMatch<&ignore::gitignore::Glob>) -> Self {
-        if mat.is_ignore() {
-            Self::Ignore
-        } else if mat.is_whitelist() {
-            Self::Whitelist
-        } else {
-            Self::None
-        }
+impl IgnoreRule {
+    fn from_match(mat: IgnoreMatch<&Glob>) -> Option<Self> {
+        let glob = match mat {
+            IgnoreMatch::None => return None,
+            IgnoreMatch::Ignore(glob) | IgnoreMatch::Whitelist(glob) => glob,
+        };
+        Some(Self {
+            pattern: glob.original().to_string(),
+            source: glob.from().map(Path::to_path_buf),
+            whitelist: glob.is_whitelist(),
+        })
+    }
+
+    /// Looks up the (1-based) line of the ignore file that this rule came from.
+    pub fn line(&self) -> Option<usize> {
+        let contents = std::fs::read_to_string(self.source.as_ref()?).ok()?;
+        // later duplicates take precedence in ignore files
+        let lines = contents.lines().collect::<Vec<_>>();
+        let index = lines
+            .iter()
+            .rposition(|line| line.trim_end() == self.pattern)?;
+        Some(index + 1)
     }
+}
 
-    const fn is_ignore(self) -> bool {
-        matches!(self, Self::Ignore)
+impl std::fmt::Display for IgnoreRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`", self.pattern)?;
+        match (&self.source, self.line()) {
+            (Some(source), Some(line)) => write!(f, " ({}:{line})", source.display()),
+            (Some(source), None) => write!(f, " ({})", source.display()),
+            (None, _) => Ok(()),
+        }
     }
 }
 
@@ -201,22 +528,61 @@ impl PromptignoreMatcher {
     }
 
     fn matches(&mut self, path: &Path, root: Option<&Path>) -> bool {
-        let is_dir = false;
-        let mut decision = PromptignoreDecision::from_match(self.global_match(path, is_dir));
+        self.matching_rules(path, root, false)
+            .last()
+            .is_some_and(|rule| !rule.whitelist)
+    }
+
+    /// Whether `dir` is ignored by rules that no whitelist could override for files under it.
+    ///
+    /// Only `.promptignore`s already in reach are considered, i.e. those of `dir` and its
+    /// parents. As with `.gitignore`, those further inside an ignored directory aren't read.
+    fn ignores_whole_dir(&mut self, dir: &Path, root: Option<&Path>) -> bool {
+        let ignored = self
+            .matching_rules(dir, root, true)
+            .last()
+            .is_some_and(|rule| !rule.whitelist);
+        if !ignored {
+            return false;
+        }
+        let global_whitelists = self
+            .global
+            .as_ref()
+            .is_some_and(|matcher| matcher.num_whitelists() > 0);
+        let chain_whitelists = root.is_some_and(|root| {
+            directory_chain_within(dir, root)
+                .iter()
+                .map(PathBuf::as_path)
+                .chain(std::iter::once(dir))
+                .any(|dir| {
+                    self.matcher_for_dir(dir)
+                        .is_some_and(|matcher| matcher.num_whitelists() > 0)
+                })
+        });
+        !global_whitelists && !chain_whitelists
+    }
+
+    /// Returns every rule matching `path` in order of precedence, the last one being decisive.
+    fn matching_rules(
+        &mut self,
+        path: &Path,
+        root: Option<&Path>,
+        is_dir: bool,
+    ) -> Vec<IgnoreRule> {
+        let mut rules = Vec::new();
+        rules.extend(IgnoreRule::from_match(self.global_match(path, is_dir)));
         if let Some(root) = root {
             for dir in directory_chain_within(path, root) {
                 if let Some(matcher) = self.matcher_for_dir(&dir) {
                     let mat = matcher.matched_path_or_any_parents(path, is_dir);
-                    if !mat.is_none() {
-                        decision = PromptignoreDecision::from_match(mat);
-                    }
+                    rules.extend(IgnoreRule::from_match(mat));
                 }
             }
         }
-        decision.is_ignore()
+        rules
     }
 
-    fn global_match(&self, path: &Path, is_dir: bool) -> IgnoreMatch<&ignore::gitignore::Glob> {
+    fn global_match(&self, path: &Path, is_dir: bool) -> IgnoreMatch<&Glob> {
         self.global
             .as_ref()
             .filter(|matcher| path.starts_with(matcher.path()))
@@ -297,33 +663,11 @@ fn prompt_home_dir() -> Option<PathBuf> {
 #[cfg(test)]
 mod tests {
     use std::fs;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
 
     use anyhow::Result;
 
     use super::*;
-
-    struct TempDir {
-        path: PathBuf,
-    }
-
-    impl TempDir {
-        fn new() -> Self {
-            let unique = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("system time before unix epoch")
-                .as_nanos();
-            let path = std::env::temp_dir().join(format!("prompt-test-{unique}"));
-            Self { path }
-        }
-    }
-
-    impl Drop for TempDir {
-        fn drop(&mut self) {
-            let _ = fs::remove_dir_all(&self.path);
-        }
-    }
+    use crate::test_util::TempDir;
 
     #[derive(Default)]
     struct EnvOverride {
@@ -351,13 +695,22 @@ mod tests {
         fs::write(temp.path.join("keep.txt"), b"keep me")?;
 
         let pattern = glob::Pattern::new("target/**").expect("valid glob pattern");
-        let discovered = discover(temp.path.clone(), vec![], vec![pattern], false)?;
+        let discovered = discover(
+            temp.path.clone(),
+            vec![],
+            &DiscoverOptions {
+                exclude: vec![pattern],
+                ..Default::default()
+            },
+        )?;
 
+        // the whole directory is pruned, standing in for the file under it
         let excluded_entry = discovered
             .iter()
-            .find(|entry| entry.path.ends_with("target/excluded.txt"))
-            .expect("expected excluded file in discovery results");
+            .find(|entry| entry.path.ends_with("target"))
+            .expect("expected excluded directory in discovery results");
         assert!(excluded_entry.excluded, "absolute-path glob did not match");
+        assert_eq!(excluded_entry.pruned, Some(FileCount::one()));
 
         Ok(())
     }
@@ -365,13 +718,12 @@ mod tests {
     #[test]
     fn gitignored_files_are_skipped_by_default() -> Result<()> {
         let temp = TempDir::new();
-        fs::create_dir_all(&temp.path)?;
         fs::create_dir_all(temp.path.join(".git"))?;
         fs::write(temp.path.join(".gitignore"), b"ignored.txt\n")?;
         let ignored = temp.path.join("ignored.txt");
         fs::write(&ignored, b"skip me")?;
 
-        let discovered = discover(temp.path.clone(), vec![], vec![], false)?;
+        let discovered = discover(temp.path.clone(), vec![], &DiscoverOptions::default())?;
         assert!(discovered.iter().all(|entry| entry.path != ignored));
 
         Ok(())
@@ -380,13 +732,19 @@ mod tests {
     #[test]
     fn gitignored_files_can_be_included() -> Result<()> {
         let temp = TempDir::new();
-        fs::create_dir_all(&temp.path)?;
         fs::create_dir_all(temp.path.join(".git"))?;
         fs::write(temp.path.join(".gitignore"), b"ignored.txt\n")?;
         let ignored = temp.path.join("ignored.txt");
         fs::write(&ignored, b"include me")?;
 
-        let discovered = discover(temp.path.clone(), vec![], vec![], true)?;
+        let discovered = discover(
+            temp.path.clone(),
+            vec![],
+            &DiscoverOptions {
+                no_gitignore: true,
+                ..Default::default()
+            },
+        )?;
         assert!(discovered.iter().any(|entry| entry.path == ignored));
 
         Ok(())
@@ -395,14 +753,13 @@ mod tests {
     #[test]
     fn promptignore_marks_files_but_keeps_them_visible() -> Result<()> {
         let temp = TempDir::new();
-        fs::create_dir_all(&temp.path)?;
         fs::write(temp.path.join(".promptignore"), b"skip.me\n")?;
         let skip = temp.path.join("skip.me");
         let keep = temp.path.join("keep.me");
         fs::write(&skip, b"skip")?;
         fs::write(&keep, b"keep")?;
 
-        let discovered = discover(temp.path.clone(), vec![], vec![], false)?;
+        let discovered = discover(temp.path.clone(), vec![], &DiscoverOptions::default())?;
 
         let skip_entry = discovered
             .iter()
@@ -433,7 +790,7 @@ mod tests {
         fs::write(&ignored, b"drop")?;
         fs::write(&keep, b"keep")?;
 
-        let discovered = discover(temp.path.clone(), vec![], vec![], false)?;
+        let discovered = discover(temp.path.clone(), vec![], &DiscoverOptions::default())?;
         let ignored_entry = discovered
             .iter()
             .find(|entry| entry.path == ignored)
@@ -454,7 +811,6 @@ mod tests {
     #[test]
     fn global_promptignore_applies_when_overridden_home_matches() -> Result<()> {
         let temp_home = TempDir::new();
-        fs::create_dir_all(&temp_home.path)?;
         fs::write(temp_home.path.join(".promptignore"), b"*.bin\n")?;
         let project = temp_home.path.join("project");
         fs::create_dir_all(&project)?;
@@ -464,7 +820,7 @@ mod tests {
         fs::write(&text, b"text")?;
 
         let _guard = EnvOverride::set_path(PROMPT_HOME_OVERRIDE_ENV, &temp_home.path);
-        let discovered = discover(project, vec![], vec![], false)?;
+        let discovered = discover(project, vec![], &DiscoverOptions::default())?;
 
         let binary_entry = discovered
             .iter()
@@ -482,4 +838,228 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn type_filters_select_and_negate_file_types() -> Result<()> {
+        let temp = TempDir::new();
+        let rust = temp.path.join("main.rs");
+        let markdown = temp.path.join("README.md");
+        let custom = temp.path.join("notes.prompt");
+        fs::write(&rust, b"fn main() {}")?;
+        fs::write(&markdown, b"# readme")?;
+        fs::write(&custom, b"notes")?;
+
+        let discovered = discover(
+            temp.path.clone(),
+            vec![],
+            &DiscoverOptions {
+                types: vec!["rust".to_string(), "prompt".to_string()],
+                type_add: vec!["prompt:*.prompt".to_string()],
+                ..Default::default()
+            },
+        )?;
+        let paths: Vec<_> = discovered.iter().map(|entry| &entry.path).collect();
+        assert_eq!(paths, vec![&rust, &custom]);
+
+        let discovered = discover(
+            temp.path.clone(),
+            vec![],
+            &DiscoverOptions {
+                types_not: vec!["markdown".to_string()],
+                ..Default::default()
+            },
+        )?;
+        assert!(discovered.iter().all(|entry| entry.path != markdown));
+        assert!(discovered.iter().any(|entry| entry.path == rust));
+
+        Ok(())
+    }
+
+    #[test]
+    fn explain_reports_deciding_rules() -> Result<()> {
+        let temp = TempDir::new();
+        fs::create_dir_all(temp.path.join(".git"))?;
+        fs::create_dir_all(temp.path.join("logs"))?;
+        fs::write(temp.path.join(".gitignore"), b"# comment\nignored.txt\n")?;
+        fs::write(temp.path.join(".promptignore"), b"logs/\n")?;
+        fs::write(temp.path.join("logs/.promptignore"), b"!keep.log\n")?;
+        let ignored = temp.path.join("ignored.txt");
+        let dropped = temp.path.join("logs/dropped.log");
+        let keep = temp.path.join("logs/keep.log");
+        for path in [&ignored, &dropped, &keep] {
+            fs::write(path, b"text")?;
+        }
+        let paths = vec![temp.path.clone()];
+        let options = DiscoverOptions::default();
+
+        let Explanation::Gitignored(rule) = explain(&ignored, &paths, &options) else {
+            panic!("ignored.txt should be explained by .gitignore");
+        };
+        assert_eq!(rule.pattern, "ignored.txt");
+        assert_eq!(rule.line(), Some(2));
+
+        let Explanation::Promptignored { rule, .. } = explain(&dropped, &paths, &options) else {
+            panic!("dropped.log should be explained by .promptignore");
+        };
+        assert_eq!(rule.pattern, "logs/");
+
+        let Explanation::Discovered {
+            whitelisted: Some((rule, overrides)),
+            ..
+        } = explain(&keep, &paths, &options)
+        else {
+            panic!("keep.log should be whitelisted");
+        };
+        assert_eq!(rule.pattern, "!keep.log");
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].pattern, "logs/");
+
+        Ok(())
+    }
+
+    #[test]
+    fn pruned_directories_match_the_unpruned_walk() -> Result<()> {
+        let temp = TempDir::new();
+        fs::create_dir_all(temp.path.join("target/debug"))?;
+        fs::create_dir_all(temp.path.join("web/node_modules/left-pad"))?;
+        fs::write(temp.path.join("target/debug/app"), b"binary")?;
+        fs::write(temp.path.join("web/node_modules/left-pad/index.js"), b"pad")?;
+        fs::write(temp.path.join("web/app.js"), b"app")?;
+        fs::write(temp.path.join("main.rs"), b"fn main() {}")?;
+
+        fs::create_dir_all(temp.path.join("docs/lib.rs"))?;
+        fs::write(temp.path.join("docs/lib.rs/guide.md"), b"guide")?;
+
+        // globs covering a whole directory prune it, others are only matched per file as before
+        for (form, prunes, kept_files) in [
+            ("target/**", true, 4),
+            ("**/node_modules/**", true, 4),
+            ("target", false, 5),
+            ("docs", false, 5),
+            ("**/*.rs", false, 4),
+        ] {
+            let options = DiscoverOptions {
+                exclude: vec![glob::Pattern::new(form).expect("valid glob pattern")],
+                ..Default::default()
+            };
+            let pruned = discover_pruning(temp.path.clone(), vec![], &options, true)?;
+            let unpruned = discover_pruning(temp.path.clone(), vec![], &options, false)?;
+            assert_eq!(
+                pruned.iter().any(|entry| entry.pruned.is_some()),
+                prunes,
+                "{form}"
+            );
+            let kept = |discovered: &[DiscoveredFile]| {
+                discovered
+                    .iter()
+                    .filter(|entry| !entry.excluded)
+                    .map(|entry| entry.path.clone())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(kept(&pruned), kept(&unpruned), "{form}");
+            assert_eq!(kept(&pruned).len(), kept_files, "{form}");
+            // a pruned directory counts the files excluded under it in the full walk
+            for dir in pruned.iter().filter(|entry| entry.pruned.is_some()) {
+                let files = unpruned
+                    .iter()
+                    .filter(|entry| entry.excluded && entry.path.starts_with(&dir.path))
+                    .count();
+                assert_eq!(
+                    dir.pruned,
+                    Some(FileCount {
+                        files,
+                        capped: false
+                    }),
+                    "{} with {form}",
+                    dir.path.display()
+                );
+            }
+            for entry in unpruned.iter().filter(|entry| entry.excluded) {
+                assert!(
+                    pruned.iter().any(|other| other.path == entry.path
+                        || other.pruned.is_some() && entry.path.starts_with(&other.path)),
+                    "{} with {form}",
+                    entry.path.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn excluded_directories_are_pruned_into_one_entry() -> Result<()> {
+        let temp = TempDir::new();
+        fs::create_dir_all(temp.path.join("vendor/nested"))?;
+        fs::create_dir_all(temp.path.join("logs"))?;
+        fs::write(temp.path.join(".promptignore"), b"vendor/\nlogs/\n")?;
+        fs::write(temp.path.join("vendor/a.txt"), b"a")?;
+        fs::write(temp.path.join("vendor/nested/b.txt"), b"b")?;
+        // out of reach once vendor/ is pruned, as with .gitignore
+        fs::write(temp.path.join("vendor/nested/.promptignore"), b"!b.txt\n")?;
+        fs::write(temp.path.join("logs/.promptignore"), b"!keep.log\n")?;
+        fs::write(temp.path.join("logs/drop.log"), b"drop")?;
+        fs::write(temp.path.join("logs/keep.log"), b"keep")?;
+        let out = temp.path.join("out");
+        fs::create_dir_all(&out)?;
+        fs::write(out.join("report.txt"), b"report")?;
+        fs::write(out.join("report.md"), b"report")?;
+
+        let discovered = discover(
+            temp.path.clone(),
+            vec![],
+            &DiscoverOptions {
+                exclude: vec![glob::Pattern::new("out/*.txt").expect("valid glob pattern")],
+                ..Default::default()
+            },
+        )?;
+
+        let vendor = discovered
+            .iter()
+            .find(|entry| entry.path == temp.path.join("vendor"))
+            .expect("vendor should be pruned into one entry");
+        assert!(vendor.excluded);
+        assert_eq!(
+            vendor.pruned,
+            Some(FileCount {
+                files: 3,
+                capped: false
+            })
+        );
+        assert!(
+            discovered
+                .iter()
+                .all(|entry| !entry.path.starts_with(temp.path.join("vendor/nested")))
+        );
+        let Explanation::Promptignored { rule, .. } = explain(
+            &temp.path.join("vendor/nested/b.txt"),
+            std::slice::from_ref(&temp.path),
+            &DiscoverOptions::default(),
+        ) else {
+            panic!("b.txt should be explained by the pruned vendor/");
+        };
+        assert_eq!(rule.pattern, "vendor/");
+
+        // the .promptignore of logs/ itself may whitelist files, so it's walked file by file
+        let keep = discovered
+            .iter()
+            .find(|entry| entry.path == temp.path.join("logs/keep.log"))
+            .expect("keep.log should be discovered");
+        assert!(!keep.excluded);
+        assert_eq!(keep.pruned, None);
+
+        // globs that don't cover a whole directory are still applied per file
+        let report = discovered
+            .iter()
+            .find(|entry| entry.path == out.join("report.txt"))
+            .expect("report.txt should be discovered");
+        assert!(report.excluded);
+        assert!(
+            discovered
+                .iter()
+                .any(|entry| entry.path == out.join("report.md"))
+        );
+
+        Ok(())
+    }
 }
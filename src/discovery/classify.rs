@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use ignore::Match as IgnoreMatch;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::files::denies_sensitive;
+
+/// Bytes read from the start of files to look for generated headers and long lines.
+const HEAD_BYTES: u64 = 8 * 1024;
+/// Bytes read from the end of files to look for sourcemap comments.
+const TAIL_BYTES: u64 = 512;
+/// Lines at the start of files searched for generated markers.
+const HEADER_LINES: usize = 5;
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT"];
+const VENDORED_DIRECTORIES: &[&str] = &["vendor", "third_party", "third-party"];
+/// Average line length above which scripts and stylesheets are taken to be minified.
+const MINIFIED_LINE_LENGTH: usize = 250;
+const MINIFIABLE_EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "css"];
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/v1\n";
+/// Git LFS pointers are always smaller than this.
+const LFS_POINTER_MAX_BYTES: u64 = 1024;
+
+/// Kinds of files that rarely help in a prompt, and are excluded unless asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileClass {
+    #[strum(serialize = "generated")]
+    Generated,
+    #[strum(serialize = "vendored")]
+    Vendored,
+    #[strum(serialize = "minified")]
+    Minified,
+    /// A pointer to content stored in Git LFS, rather than the content itself
+    #[strum(serialize = "Git LFS pointer")]
+    LfsPointer,
+}
+
+/// The class of a file and what gave it away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub class: FileClass,
+    pub reason: String,
+}
+
+impl Classification {
+    fn new(class: FileClass, reason: impl Into<String>) -> Self {
+        Self {
+            class,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// `linguist-generated` and `linguist-vendored` attributes from a `.gitattributes`, as ignore
+/// matchers where setting an attribute ignores and unsetting it whitelists.
+#[derive(Clone)]
+struct Gitattributes {
+    source: PathBuf,
+    generated: Gitignore,
+    vendored: Gitignore,
+}
+
+/// Classifies files, caching the `.gitattributes` of each directory.
+pub(super) struct Classifier {
+    gitattributes: HashMap<PathBuf, Option<Gitattributes>>,
+    /// Sensitive files that may be read, as allowed with `--allow-sensitive`.
+    allow_sensitive: Vec<glob::Pattern>,
+}
+
+impl Classifier {
+    pub fn new(allow_sensitive: &[glob::Pattern]) -> Self {
+        Self {
+            gitattributes: HashMap::new(),
+            allow_sensitive: allow_sensitive.to_vec(),
+        }
+    }
+
+    /// Classifies the file at the absolute `path`, whose path relative to the discovered paths
+    /// is `relative`.
+    ///
+    /// The path and `.gitattributes` are checked first, and the file's content is only read if
+    /// they don't settle its class. Files on the sensitive file denylist are never read, unless
+    /// allowed.
+    pub fn classify(&mut self, path: &Path, relative: &Path) -> Option<Classification> {
+        if let Some(classification) = self.classify_by_path(path, relative) {
+            return Some(classification);
+        }
+        if denies_sensitive(relative, &self.allow_sensitive).is_some() {
+            return None;
+        }
+        let (head, tail, size) = read_ends(path).ok()?;
+        if size < LFS_POINTER_MAX_BYTES && head.starts_with(LFS_POINTER_PREFIX) {
+            return Some(Classification::new(
+                FileClass::LfsPointer,
+                "Git LFS pointer contents",
+            ));
+        }
+        let head = String::from_utf8_lossy(&head);
+        if let Some(marker) = generated_marker(&head) {
+            return Some(Classification::new(
+                FileClass::Generated,
+                format!("`{marker}` header"),
+            ));
+        }
+        minified_content(path, &head, &String::from_utf8_lossy(&tail))
+    }
+
+    /// Classifies a file by its `.gitattributes`, its directories and its name alone.
+    fn classify_by_path(&mut self, path: &Path, relative: &Path) -> Option<Classification> {
+        if let Some(classification) = self.linguist_attribute(path) {
+            return Some(classification);
+        }
+        if let Some(dir) = relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .map(|component| component.as_os_str())
+            .find(|dir| {
+                VENDORED_DIRECTORIES
+                    .iter()
+                    .any(|vendored| *dir == *vendored)
+            })
+        {
+            return Some(Classification::new(
+                FileClass::Vendored,
+                format!("under a {}/ directory", dir.to_string_lossy()),
+            ));
+        }
+        let extension = minifiable_extension(path)?;
+        path.file_name()?
+            .to_str()?
+            .ends_with(&format!(".min.{extension}"))
+            .then(|| Classification::new(FileClass::Minified, "`.min` file name"))
+    }
+
+    /// Looks up `linguist-generated` and `linguist-vendored` in the `.gitattributes` of the
+    /// file's directory and its parents, up to the repository root.
+    fn linguist_attribute(&mut self, path: &Path) -> Option<Classification> {
+        let mut decided = (false, false);
+        for dir in path.ancestors().skip(1) {
+            if let Some(attributes) = self.attributes_for_dir(dir) {
+                let matchers = [
+                    (FileClass::Generated, &attributes.generated, &mut decided.0),
+                    (FileClass::Vendored, &attributes.vendored, &mut decided.1),
+                ];
+                for (class, matcher, decided) in matchers {
+                    if *decided {
+                        continue;
+                    }
+                    match matcher.matched_path_or_any_parents(path, false) {
+                        IgnoreMatch::None => {}
+                        IgnoreMatch::Whitelist(_) => *decided = true,
+                        IgnoreMatch::Ignore(_) => {
+                            return Some(Classification::new(
+                                class,
+                                format!("`linguist-{class}` in {}", attributes.source.display()),
+                            ));
+                        }
+                    }
+                }
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        None
+    }
+
+    fn attributes_for_dir(&mut self, dir: &Path) -> Option<Gitattributes> {
+        if !self.gitattributes.contains_key(dir) {
+            let attributes = load_gitattributes(dir);
+            self.gitattributes.insert(dir.to_path_buf(), attributes);
+        }
+        self.gitattributes.get(dir).and_then(Clone::clone)
+    }
+}
+
+fn load_gitattributes(dir: &Path) -> Option<Gitattributes> {
+    let source = dir.join(".gitattributes");
+    let contents = std::fs::read_to_string(&source).ok()?;
+    let mut generated = GitignoreBuilder::new(dir);
+    let mut vendored = GitignoreBuilder::new(dir);
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next().filter(|pattern| !pattern.starts_with('#')) else {
+            continue;
+        };
+        for attribute in fields {
+            let (name, set) = match attribute.split_once('=') {
+                Some((name, value)) => (name, value != "false"),
+                None => attribute
+                    .strip_prefix(['-', '!'])
+                    .map_or((attribute, true), |name| (name, false)),
+            };
+            let builder = match name {
+                "linguist-generated" => &mut generated,
+                "linguist-vendored" => &mut vendored,
+                _ => continue,
+            };
+            let line = if set {
+                pattern.to_string()
+            } else {
+                format!("!{pattern}")
+            };
+            if let Err(err) = builder.add_line(Some(source.clone()), &line) {
+                warn!("Failed to parse {}: {err}", source.display());
+            }
+        }
+    }
+    Some(Gitattributes {
+        generated: generated.build().ok()?,
+        vendored: vendored.build().ok()?,
+        source,
+    })
+}
+
+/// Reads the start and end of a file, along with its size.
+fn read_ends(path: &Path) -> std::io::Result<(Vec<u8>, Vec<u8>, u64)> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut head = vec![];
+    (&mut file).take(HEAD_BYTES).read_to_end(&mut head)?;
+    let mut tail = vec![];
+    if size > HEAD_BYTES {
+        file.seek(SeekFrom::End(-(TAIL_BYTES.min(size) as i64)))?;
+        file.read_to_end(&mut tail)?;
+    } else {
+        tail.clone_from(&head);
+    }
+    Ok((head, tail, size))
+}
+
+/// Finds a generated marker in a comment among the first lines.
+fn generated_marker(head: &str) -> Option<&'static str> {
+    const COMMENT_PREFIXES: &[&str] = &["//", "#", "/*", "*", "<!--", "--", ";", "%"];
+    head.lines()
+        .take(HEADER_LINES)
+        .map(str::trim_start)
+        .filter(|line| {
+            COMMENT_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .find_map(|line| {
+            GENERATED_MARKERS
+                .iter()
+                .copied()
+                .find(|marker| line.contains(marker))
+        })
+}
+
+/// The extension of a script or stylesheet that may be minified.
+fn minifiable_extension(path: &Path) -> Option<&str> {
+    path.extension()?
+        .to_str()
+        .filter(|extension| MINIFIABLE_EXTENSIONS.contains(extension))
+}
+
+fn minified_content(path: &Path, head: &str, tail: &str) -> Option<Classification> {
+    minifiable_extension(path)?;
+    if tail.lines().rev().take(2).any(|line| {
+        let line = line.trim_start();
+        line.starts_with("//# sourceMappingURL=") || line.starts_with("/*# sourceMappingURL=")
+    }) {
+        return Some(Classification::new(
+            FileClass::Minified,
+            "sourcemap comment",
+        ));
+    }
+    let lines = head.lines().count();
+    let average = head.len() / lines.max(1);
+    (average > MINIFIED_LINE_LENGTH).then(|| {
+        Classification::new(
+            FileClass::Minified,
+            format!("average line length of {average}"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn files_are_classified_by_attributes_paths_and_contents() -> Result<()> {
+        let temp = TempDir::new();
+        fs::create_dir_all(temp.path.join(".git"))?;
+        fs::create_dir_all(temp.path.join("third_party/lib"))?;
+        fs::create_dir_all(temp.path.join("api"))?;
+        fs::write(
+            temp.path.join(".gitattributes"),
+            "*.pb.go linguist-generated\napi/keep.pb.go -linguist-generated\n",
+        )?;
+        let files = [
+            ("api/service.pb.go", "package api\n"),
+            ("api/keep.pb.go", "package api\n"),
+            ("third_party/lib/util.c", "int x;\n"),
+            ("schema.rs", "// @generated by diesel\npub mod schema {}\n"),
+            ("bundle.js", &format!("{}\n", "a=1;".repeat(200))),
+            ("app.js", "let a = 1;\n//# sourceMappingURL=app.js.map\n"),
+            (
+                "model.bin",
+                "version https://git-lfs.github.com/spec/v1\noid sha256:abc\nsize 12\n",
+            ),
+            ("main.rs", "fn main() {}\n"),
+            (".env", "# @generated by setup\nTOKEN=secret\n"),
+        ];
+        for (name, contents) in files {
+            fs::write(temp.path.join(name), contents)?;
+        }
+
+        let mut classifier = Classifier::new(&[]);
+        let mut class = |name: &str| {
+            classifier
+                .classify(&temp.path.join(name), Path::new(name))
+                .map(|classification| classification.class)
+        };
+        assert_eq!(class("api/service.pb.go"), Some(FileClass::Generated));
+        assert_eq!(class("api/keep.pb.go"), None);
+        assert_eq!(class("third_party/lib/util.c"), Some(FileClass::Vendored));
+        assert_eq!(class("schema.rs"), Some(FileClass::Generated));
+        assert_eq!(class("bundle.js"), Some(FileClass::Minified));
+        assert_eq!(class("app.js"), Some(FileClass::Minified));
+        assert_eq!(class("model.bin"), Some(FileClass::LfsPointer));
+        assert_eq!(class("main.rs"), None);
+        // sensitive files aren't read, and the path settles the class without reading
+        assert_eq!(class(".env"), None);
+        assert_eq!(
+            class("third_party/lib/unreadable.c"),
+            Some(FileClass::Vendored)
+        );
+        // allowed sensitive files are read, as they will be when included
+        let mut classifier = Classifier::new(&[glob::Pattern::new(".env").expect("valid glob")]);
+        assert_eq!(
+            classifier
+                .classify(&temp.path.join(".env"), Path::new(".env"))
+                .map(|classification| classification.class),
+            Some(FileClass::Generated)
+        );
+
+        Ok(())
+    }
+}
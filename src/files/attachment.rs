@@ -0,0 +1,181 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::imageops::FilterType;
+use image::{ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+
+/// Largest image accepted by default, matching the Anthropic API's limit.
+pub const DEFAULT_MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// Longest edge images are resized to by the Anthropic API before being tokenized.
+const PROVIDER_MAX_EDGE: f64 = 1568.0;
+/// Most pixels images are resized to by the Anthropic API before being tokenized.
+const PROVIDER_MAX_PIXELS: f64 = 1_150_000.0;
+const PIXELS_PER_TOKEN: f64 = 750.0;
+
+/// Options for attaching images to the prompt.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageOptions {
+    /// Images larger than this, after any downscaling, are left out.
+    pub max_bytes: u64,
+    /// Images with a longer edge are downscaled to fit, if set.
+    pub max_dimension: Option<u32>,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_IMAGE_BYTES,
+            max_dimension: None,
+        }
+    }
+}
+
+/// An image attached to the prompt for multimodal models, serialized as a chat API image
+/// content block: `{"type": "image", "source": {"type": "base64", "media_type", "data"}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "image")]
+pub struct ImageAttachment {
+    pub source: ImageSource,
+    /// Dimensions after any downscaling, which aren't part of the content block.
+    #[serde(skip)]
+    pub width: u32,
+    #[serde(skip)]
+    pub height: u32,
+}
+
+/// The base64 source of an image content block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "base64")]
+pub struct ImageSource {
+    pub media_type: String,
+    /// Base64 encoded image data.
+    pub data: String,
+}
+
+impl ImageAttachment {
+    /// Estimates the tokens the image costs, following Anthropic's documented formula of
+    /// `width * height / 750` after images are resized to fit the API's limits.
+    pub fn estimated_tokens(&self) -> usize {
+        let (width, height) = (f64::from(self.width), f64::from(self.height));
+        let scale = (PROVIDER_MAX_EDGE / width.max(height))
+            .min((PROVIDER_MAX_PIXELS / (width * height)).sqrt())
+            .min(1.0);
+        ((width * scale) * (height * scale) / PIXELS_PER_TOKEN).ceil() as usize
+    }
+}
+
+/// Whether images of the MIME type can be attached.
+pub(super) fn is_attachable(mime: &str) -> bool {
+    matches!(
+        mime,
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp"
+    )
+}
+
+/// Prepares an image for attaching, downscaling it if needed. Returns `None` if it is too large
+/// even so.
+pub(super) fn attach(
+    bytes: Vec<u8>,
+    media_type: &str,
+    options: &ImageOptions,
+) -> Result<Option<ImageAttachment>> {
+    let reader = ImageReader::new(Cursor::new(&bytes)).with_guessed_format()?;
+    let (width, height) = reader.into_dimensions()?;
+    let (bytes, media_type, width, height) = match options.max_dimension {
+        Some(max_dimension) if width.max(height) > max_dimension => {
+            downscale(&bytes, media_type, max_dimension)?
+        }
+        _ => (bytes, media_type.to_string(), width, height),
+    };
+    if bytes.len() as u64 > options.max_bytes {
+        return Ok(None);
+    }
+    Ok(Some(ImageAttachment {
+        source: ImageSource {
+            media_type,
+            data: STANDARD.encode(bytes),
+        },
+        width,
+        height,
+    }))
+}
+
+/// Resizes an image so its longer edge is `max_dimension`, keeping JPEGs as JPEGs and encoding
+/// other formats as PNG.
+fn downscale(
+    bytes: &[u8],
+    media_type: &str,
+    max_dimension: u32,
+) -> Result<(Vec<u8>, String, u32, u32)> {
+    let image =
+        image::load_from_memory(bytes)?.resize(max_dimension, max_dimension, FilterType::Triangle);
+    let format = if media_type == "image/jpeg" {
+        ImageFormat::Jpeg
+    } else {
+        ImageFormat::Png
+    };
+    let mut encoded = Cursor::new(vec![]);
+    image.write_to(&mut encoded, format)?;
+    Ok((
+        encoded.into_inner(),
+        format.to_mime_type().to_string(),
+        image.width(),
+        image.height(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, RgbImage};
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut encoded = Cursor::new(vec![]);
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut encoded, ImageFormat::Png)
+            .expect("should encode png");
+        encoded.into_inner()
+    }
+
+    #[test]
+    fn images_are_downscaled_and_estimated() -> Result<()> {
+        let options = ImageOptions {
+            max_dimension: Some(100),
+            ..Default::default()
+        };
+        let attachment = attach(png(400, 200), "image/png", &options)?.expect("within size cap");
+        assert_eq!((attachment.width, attachment.height), (100, 50));
+        assert_eq!(attachment.source.media_type, "image/png");
+        assert_eq!(attachment.estimated_tokens(), 7);
+
+        let block = serde_json::to_value(&attachment)?;
+        assert_eq!(block["type"], "image");
+        assert_eq!(block["source"]["type"], "base64");
+        assert_eq!(block["source"]["media_type"], "image/png");
+        assert!(block["source"]["data"].is_string());
+        assert!(block.get("width").is_none());
+
+        let large = ImageAttachment {
+            source: ImageSource {
+                media_type: "image/png".to_string(),
+                data: String::new(),
+            },
+            width: 4000,
+            height: 3000,
+        };
+        // resized to fit 1.15 megapixels before being tokenized
+        assert_eq!(large.estimated_tokens(), 1534);
+
+        let tiny_cap = ImageOptions {
+            max_bytes: 10,
+            max_dimension: None,
+        };
+        assert!(attach(png(10, 10), "image/png", &tiny_cap)?.is_none());
+
+        Ok(())
+    }
+}
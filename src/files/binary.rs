@@ -0,0 +1,219 @@
+use std::fs::OpenOptions;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::Result;
+use encoding_rs::Encoding;
+
+use super::{encoding, strip_dot_prefix};
+
+/// Bytes sampled from the start of files by default.
+pub const DEFAULT_SAMPLE_BYTES: usize = 8 * 1024;
+/// Share of control characters above which a sample without null bytes is taken as binary.
+const MAX_CONTROL_SHARE: f64 = 0.1;
+const TEXTUAL_MIME_PREFIX: &str = "text/";
+
+/// Settings for judging whether files are binary.
+#[derive(Debug, Clone)]
+pub struct BinaryDetection {
+    /// Files always read as text, whatever their content.
+    pub text: Vec<glob::Pattern>,
+    /// Files always taken as binary, whatever their content.
+    pub binary: Vec<glob::Pattern>,
+    /// Bytes sampled from the start of each file.
+    pub sample_bytes: usize,
+}
+
+impl Default for BinaryDetection {
+    fn default() -> Self {
+        Self {
+            text: vec![],
+            binary: vec![],
+            sample_bytes: DEFAULT_SAMPLE_BYTES,
+        }
+    }
+}
+
+/// Whether a file was judged to be binary, the MIME type detected from its magic bytes, and the
+/// rule that decided it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryVerdict {
+    pub binary: bool,
+    pub mime: Option<&'static str>,
+    pub rule: BinaryRule,
+}
+
+/// The rule that classified a file as binary or text.
+#[derive(Debug, Clone, PartialEq, Eq, strum::Display)]
+pub enum BinaryRule {
+    #[strum(to_string = "forced to text by --text `{0}`")]
+    ForcedText(String),
+    #[strum(to_string = "forced to binary by --binary `{0}`")]
+    ForcedBinary(String),
+    #[strum(to_string = "textual MIME type from magic bytes")]
+    TextualMime,
+    #[strum(to_string = "binary MIME type from magic bytes")]
+    BinaryMime,
+    /// Magic bytes matched a binary format, but the sample is plain UTF-8 text, as with source
+    /// files whose first bytes happen to look like a signature.
+    #[strum(to_string = "magic bytes overruled, the sample is plain UTF-8 text")]
+    MimeOverruledByText,
+    #[strum(to_string = "byte order mark")]
+    ByteOrderMark,
+    #[strum(to_string = "UTF-16 text without a byte order mark")]
+    Utf16,
+    #[strum(to_string = "null bytes in the sample")]
+    NullBytes,
+    #[strum(to_string = "{0}% control characters in the sample")]
+    ControlCharacters(u8),
+    #[strum(to_string = "no binary markers in the sample")]
+    PlainText,
+}
+
+/// Reads the start of the file at `path` to judge whether it is binary.
+pub fn sniff_binary(path: &Path, detection: &BinaryDetection) -> Result<BinaryVerdict> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    let mut reader = BufReader::new(file).take(detection.sample_bytes as u64);
+    let mut sample = Vec::with_capacity(detection.sample_bytes);
+    reader.read_to_end(&mut sample)?;
+    Ok(judge_sample(path, &sample, detection))
+}
+
+/// Judges whether the file at `path` is binary from a sample of its start, which is cut to
+/// `detection.sample_bytes`.
+pub(super) fn judge_sample(
+    path: &Path,
+    sample: &[u8],
+    detection: &BinaryDetection,
+) -> BinaryVerdict {
+    let sample = &sample[..sample.len().min(detection.sample_bytes)];
+    let verdict = is_probably_binary(sample);
+
+    let forced = |globs: &[glob::Pattern]| {
+        let path = strip_dot_prefix(path);
+        globs
+            .iter()
+            .find(|glob| glob.matches_path(path))
+            .map(|glob| glob.as_str().to_string())
+    };
+    if let Some(glob) = forced(&detection.text) {
+        return BinaryVerdict {
+            binary: false,
+            rule: BinaryRule::ForcedText(glob),
+            ..verdict
+        };
+    }
+    if let Some(glob) = forced(&detection.binary) {
+        return BinaryVerdict {
+            binary: true,
+            rule: BinaryRule::ForcedBinary(glob),
+            ..verdict
+        };
+    }
+    verdict
+}
+
+pub(super) fn is_probably_binary(sample: &[u8]) -> BinaryVerdict {
+    let verdict = |binary, mime, rule| BinaryVerdict { binary, mime, rule };
+
+    if let Some(kind) = infer::get(sample) {
+        let mime = kind.mime_type();
+        if mime.starts_with(TEXTUAL_MIME_PREFIX)
+            || is_textual_mime(mime)
+            || kind.matcher_type() == infer::MatcherType::Text
+        {
+            return verdict(false, Some(mime), BinaryRule::TextualMime);
+        }
+        if is_plain_utf8(sample) {
+            return verdict(false, Some(mime), BinaryRule::MimeOverruledByText);
+        }
+        return verdict(true, Some(mime), BinaryRule::BinaryMime);
+    }
+
+    if Encoding::for_bom(sample).is_some() {
+        return verdict(false, None, BinaryRule::ByteOrderMark);
+    }
+    if encoding::sniff_utf16(sample).is_some() {
+        return verdict(false, None, BinaryRule::Utf16);
+    }
+    if sample.contains(&0) {
+        return verdict(true, None, BinaryRule::NullBytes);
+    }
+    let share = control_share(sample);
+    if share > MAX_CONTROL_SHARE {
+        let percent = (share * 100.0).round() as u8;
+        return verdict(true, None, BinaryRule::ControlCharacters(percent));
+    }
+    verdict(false, None, BinaryRule::PlainText)
+}
+
+fn is_textual_mime(mime: &str) -> bool {
+    matches!(
+        mime,
+        "application/json"
+            | "application/xml"
+            | "application/javascript"
+            | "application/graphql"
+            | "application/sql"
+            | "image/svg+xml"
+    )
+}
+
+/// Share of bytes that are control characters other than whitespace and escapes.
+fn control_share(sample: &[u8]) -> f64 {
+    if sample.is_empty() {
+        return 0.0;
+    }
+    let controls = sample
+        .iter()
+        .filter(|&&byte| {
+            (byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | b'\x0c' | b'\x1b'))
+                || byte == 0x7f
+        })
+        .count();
+    controls as f64 / sample.len() as f64
+}
+
+/// Whether the sample is UTF-8 without control characters, allowing for a character cut off at
+/// its end.
+fn is_plain_utf8(sample: &[u8]) -> bool {
+    let valid = match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    };
+    valid && control_share(sample) == 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_are_classified_by_the_first_matching_rule() {
+        let rule = |sample: &[u8]| {
+            let verdict = is_probably_binary(sample);
+            (verdict.binary, verdict.rule)
+        };
+
+        assert_eq!(rule(b"fn main() {}\n"), (false, BinaryRule::PlainText));
+        assert_eq!(
+            rule(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            (true, BinaryRule::BinaryMime)
+        );
+        // "BM" is the signature of bitmap images
+        assert_eq!(
+            rule(b"BMI = weight / height^2\n"),
+            (false, BinaryRule::MimeOverruledByText)
+        );
+        assert_eq!(rule(b"\0\0\0\0abcd"), (true, BinaryRule::NullBytes));
+        assert_eq!(
+            rule(b"\x01\x02\x03\x04data"),
+            (true, BinaryRule::ControlCharacters(50))
+        );
+        assert_eq!(rule(b"\xff\xfea\0b\0"), (false, BinaryRule::ByteOrderMark));
+        assert_eq!(
+            rule(b"\x1b[31mred\x1b[0m\n"),
+            (false, BinaryRule::PlainText)
+        );
+    }
+}
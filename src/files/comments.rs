@@ -0,0 +1,774 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use super::Line;
+
+/// Languages whose comments can be stripped, detected from file extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Language {
+    Rust,
+    /// C, C++ and Java
+    CLike,
+    Go,
+    JavaScript,
+    Python,
+    Shell,
+    Toml,
+    Yaml,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let language = match extension.as_str() {
+            "rs" => Self::Rust,
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" | "java" => Self::CLike,
+            "go" => Self::Go,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Self::JavaScript,
+            "py" | "pyi" => Self::Python,
+            "sh" | "bash" | "zsh" => Self::Shell,
+            "toml" => Self::Toml,
+            "yaml" | "yml" => Self::Yaml,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    const fn syntax(self) -> &'static Syntax {
+        match self {
+            Self::Rust => &RUST,
+            Self::CLike => &C_LIKE,
+            Self::Go => &GO,
+            Self::JavaScript => &JAVASCRIPT,
+            Self::Python => &PYTHON,
+            Self::Shell => &SHELL,
+            Self::Toml => &TOML,
+            Self::Yaml => &YAML,
+        }
+    }
+}
+
+struct Quote {
+    open: &'static str,
+    close: &'static str,
+    escapes: bool,
+    multiline: bool,
+}
+
+const fn quote(delimiter: &'static str, escapes: bool, multiline: bool) -> Quote {
+    Quote {
+        open: delimiter,
+        close: delimiter,
+        escapes,
+        multiline,
+    }
+}
+
+struct Syntax {
+    line_comments: &'static [&'static str],
+    /// Line comment prefixes that start doc comments, checked before `line_comments`.
+    doc_line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Block comment openers that start doc comments, checked before `block_comment`.
+    doc_block_comments: &'static [&'static str],
+    nested_block_comments: bool,
+    /// Checked in order, so longer delimiters sharing a prefix must come first.
+    quotes: &'static [Quote],
+    /// Whether comments only start at the beginning of a word, e.g. `$#` isn't a shell comment.
+    comments_need_word_start: bool,
+    /// Whether quotes only start at the beginning of a word, e.g. `it's` in YAML.
+    quotes_need_word_start: bool,
+}
+
+const RUST: Syntax = Syntax {
+    line_comments: &["//"],
+    doc_line_comments: &["///", "//!"],
+    block_comment: Some(("/*", "*/")),
+    doc_block_comments: &["/**", "/*!"],
+    nested_block_comments: true,
+    // raw strings and char literals are handled separately
+    quotes: &[quote("\"", true, true)],
+    comments_need_word_start: false,
+    quotes_need_word_start: false,
+};
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    doc_line_comments: &["///", "//!"],
+    block_comment: Some(("/*", "*/")),
+    doc_block_comments: &["/**", "/*!"],
+    nested_block_comments: false,
+    quotes: &[
+        quote("\"\"\"", true, true),
+        quote("\"", true, false),
+        quote("'", true, false),
+    ],
+    comments_need_word_start: false,
+    quotes_need_word_start: false,
+};
+
+const GO: Syntax = Syntax {
+    line_comments: &["//"],
+    doc_line_comments: &[],
+    block_comment: Some(("/*", "*/")),
+    doc_block_comments: &[],
+    nested_block_comments: false,
+    quotes: &[
+        quote("\"", true, false),
+        quote("'", true, false),
+        quote("`", false, true),
+    ],
+    comments_need_word_start: false,
+    quotes_need_word_start: false,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    line_comments: &["//"],
+    doc_line_comments: &[],
+    block_comment: Some(("/*", "*/")),
+    doc_block_comments: &["/**"],
+    nested_block_comments: false,
+    quotes: &[
+        quote("\"", true, false),
+        quote("'", true, false),
+        quote("`", true, true),
+    ],
+    comments_need_word_start: false,
+    quotes_need_word_start: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    line_comments: &["#"],
+    doc_line_comments: &[],
+    block_comment: None,
+    doc_block_comments: &[],
+    nested_block_comments: false,
+    // docstrings are handled separately
+    quotes: &[
+        quote("\"\"\"", true, true),
+        quote("'''", true, true),
+        quote("\"", true, false),
+        quote("'", true, false),
+    ],
+    comments_need_word_start: false,
+    quotes_need_word_start: false,
+};
+
+const SHELL: Syntax = Syntax {
+    line_comments: &["#"],
+    doc_line_comments: &[],
+    block_comment: None,
+    doc_block_comments: &[],
+    nested_block_comments: false,
+    quotes: &[quote("'", false, true), quote("\"", true, true)],
+    comments_need_word_start: true,
+    quotes_need_word_start: false,
+};
+
+const TOML: Syntax = Syntax {
+    line_comments: &["#"],
+    doc_line_comments: &[],
+    block_comment: None,
+    doc_block_comments: &[],
+    nested_block_comments: false,
+    quotes: &[
+        quote("\"\"\"", true, true),
+        quote("'''", false, true),
+        quote("\"", true, false),
+        quote("'", false, false),
+    ],
+    comments_need_word_start: false,
+    quotes_need_word_start: false,
+};
+
+const YAML: Syntax = Syntax {
+    line_comments: &["#"],
+    doc_line_comments: &[],
+    block_comment: None,
+    doc_block_comments: &[],
+    nested_block_comments: false,
+    quotes: &[quote("\"", true, true), quote("'", false, true)],
+    comments_need_word_start: true,
+    quotes_need_word_start: true,
+};
+
+#[derive(Debug)]
+enum State {
+    Code,
+    Block {
+        close: &'static str,
+        depth: usize,
+        keep: bool,
+    },
+    Str {
+        close: Cow<'static, str>,
+        escapes: bool,
+        multiline: bool,
+        keep: bool,
+    },
+    /// Inside a YAML `|` or `>` block scalar, indented deeper than `indent`.
+    BlockScalar {
+        indent: usize,
+    },
+    /// Inside a shell heredoc body, ended by a line holding just `delimiter`.
+    Heredoc {
+        delimiter: String,
+        /// Whether leading tabs are ignored, as with `<<-`.
+        strip_tabs: bool,
+    },
+}
+
+/// Removes comments from source code line by line, keeping string literals intact.
+pub(super) struct Stripper {
+    language: Language,
+    syntax: &'static Syntax,
+    keep_doc_comments: bool,
+    state: State,
+    /// Whether a Python string starting the next statement would be a docstring.
+    expect_docstring: bool,
+    /// Whether a `/` starting the next line would start a JavaScript regex literal.
+    expect_regex: bool,
+}
+
+impl Stripper {
+    pub const fn new(language: Language, keep_doc_comments: bool) -> Self {
+        Self {
+            language,
+            syntax: language.syntax(),
+            keep_doc_comments,
+            state: State::Code,
+            expect_docstring: true,
+            expect_regex: true,
+        }
+    }
+
+    pub fn strip<'a>(&mut self, lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        lines
+            .into_iter()
+            .filter_map(|line| {
+                if line.number.is_none() {
+                    // markers aren't part of the source
+                    return Some(line);
+                }
+                let text = match line.text {
+                    Cow::Borrowed(text) => self.strip_line(text, line.number)?,
+                    Cow::Owned(text) => {
+                        Cow::Owned(self.strip_line(&text, line.number)?.into_owned())
+                    }
+                };
+                Some(Line {
+                    number: line.number,
+                    text,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the line without comments, or `None` if nothing but comments was on it.
+    fn strip_line<'a>(&mut self, line: &'a str, number: Option<usize>) -> Option<Cow<'a, str>> {
+        if let State::BlockScalar { indent } = self.state {
+            if line.trim().is_empty() || indentation(line) > indent {
+                return Some(Cow::Borrowed(line));
+            }
+            self.state = State::Code;
+        }
+        if let State::Heredoc {
+            delimiter,
+            strip_tabs,
+        } = &self.state
+        {
+            let end = if *strip_tabs {
+                line.trim_start_matches('\t')
+            } else {
+                line
+            };
+            if end == delimiter {
+                self.state = State::Code;
+            }
+            return Some(Cow::Borrowed(line));
+        }
+        if self.language == Language::Shell && number == Some(1) && line.starts_with("#!") {
+            return Some(Cow::Borrowed(line));
+        }
+
+        let syntax = self.syntax;
+        let mut out = String::with_capacity(line.len());
+        let mut stripped = false;
+        let mut heredoc = None;
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let ch = rest.chars().next().expect("should be in bounds");
+            match &mut self.state {
+                State::Block { close, depth, keep } => {
+                    let (close, keep) = (*close, *keep);
+                    let open = syntax.block_comment.map_or("", |(open, _)| open);
+                    let token = if syntax.nested_block_comments && rest.starts_with(open) {
+                        *depth += 1;
+                        open
+                    } else if rest.starts_with(close) {
+                        *depth -= 1;
+                        if *depth == 0 {
+                            self.state = State::Code;
+                        }
+                        close
+                    } else {
+                        &rest[..ch.len_utf8()]
+                    };
+                    if keep {
+                        out.push_str(token);
+                    } else {
+                        stripped = true;
+                    }
+                    i += token.len();
+                }
+                State::Str {
+                    close,
+                    escapes,
+                    keep,
+                    ..
+                } => {
+                    let keep = *keep;
+                    let len = if *escapes && ch == '\\' {
+                        1 + rest[1..].chars().next().map_or(0, char::len_utf8)
+                    } else if rest.starts_with(close.as_ref()) {
+                        let len = close.len();
+                        self.state = State::Code;
+                        len
+                    } else {
+                        ch.len_utf8()
+                    };
+                    if keep {
+                        out.push_str(&rest[..len]);
+                    } else {
+                        stripped = true;
+                    }
+                    i += len;
+                }
+                State::Heredoc { .. } => unreachable!("heredoc lines are returned as they are"),
+                State::Code | State::BlockScalar { .. } => {
+                    let word_start = line[..i]
+                        .chars()
+                        .next_back()
+                        .is_none_or(|previous| previous.is_whitespace());
+                    let comments_allowed = word_start || !syntax.comments_need_word_start;
+
+                    if comments_allowed && is_doc_line_comment(syntax, rest) {
+                        if self.keep_doc_comments {
+                            out.push_str(rest);
+                        } else {
+                            stripped = true;
+                        }
+                        break;
+                    }
+                    if comments_allowed
+                        && syntax
+                            .line_comments
+                            .iter()
+                            .any(|prefix| rest.starts_with(prefix))
+                    {
+                        stripped = true;
+                        break;
+                    }
+                    if let Some((open, close)) = syntax.block_comment
+                        && rest.starts_with(open)
+                    {
+                        let keep = self.keep_doc_comments && is_doc_block_comment(syntax, rest);
+                        self.state = State::Block {
+                            close,
+                            depth: 1,
+                            keep,
+                        };
+                        if keep {
+                            out.push_str(open);
+                        } else {
+                            stripped = true;
+                        }
+                        i += open.len();
+                        continue;
+                    }
+                    if self.language == Language::Rust
+                        && let Some(len) = rust_literal_len(line, i)
+                    {
+                        out.push_str(&rest[..len]);
+                        i += len;
+                        continue;
+                    }
+                    if self.language == Language::Rust
+                        && let Some((open_len, close)) = rust_raw_string_open(line, i)
+                    {
+                        self.state = State::Str {
+                            close: Cow::Owned(close),
+                            escapes: false,
+                            multiline: true,
+                            keep: true,
+                        };
+                        out.push_str(&rest[..open_len]);
+                        i += open_len;
+                        continue;
+                    }
+                    if self.language == Language::JavaScript
+                        && ch == '/'
+                        && regex_can_follow(&out).unwrap_or(self.expect_regex)
+                        && let Some(len) = regex_literal_len(rest)
+                    {
+                        out.push_str(&rest[..len]);
+                        i += len;
+                        continue;
+                    }
+                    if self.language == Language::Shell && rest.starts_with("<<") {
+                        // `<<<` is a here-string rather than a heredoc
+                        let len = if rest.starts_with("<<<") {
+                            3
+                        } else {
+                            heredoc = heredoc.or_else(|| heredoc_delimiter(&rest[2..]));
+                            2
+                        };
+                        out.push_str(&rest[..len]);
+                        i += len;
+                        continue;
+                    }
+                    let quotes_allowed = !syntax.quotes_need_word_start
+                        || line[..i].chars().next_back().is_none_or(|previous| {
+                            previous.is_whitespace() || "[{,:-".contains(previous)
+                        });
+                    let quote = syntax
+                        .quotes
+                        .iter()
+                        .find(|quote| quotes_allowed && rest.starts_with(quote.open));
+                    if let Some(quote) = quote {
+                        let docstring = self.language == Language::Python
+                            && self.expect_docstring
+                            && quote.multiline
+                            && out.trim().is_empty();
+                        let keep = !docstring || self.keep_doc_comments;
+                        self.state = State::Str {
+                            close: Cow::Borrowed(quote.close),
+                            escapes: quote.escapes,
+                            multiline: quote.multiline,
+                            keep,
+                        };
+                        if keep {
+                            out.push_str(quote.open);
+                        } else {
+                            stripped = true;
+                        }
+                        i += quote.open.len();
+                        continue;
+                    }
+                    out.push(ch);
+                    i += ch.len_utf8();
+                }
+            }
+        }
+
+        if let State::Str {
+            multiline: false, ..
+        } = self.state
+        {
+            // unterminated single line strings are most likely a misdetection
+            self.state = State::Code;
+        }
+        if let Some((delimiter, strip_tabs)) = heredoc
+            && matches!(self.state, State::Code)
+        {
+            self.state = State::Heredoc {
+                delimiter,
+                strip_tabs,
+            };
+        }
+        let code = out.trim_end();
+        if !code.trim().is_empty() {
+            if self.language == Language::Python {
+                self.expect_docstring = code.ends_with(':');
+            }
+            if self.language == Language::JavaScript {
+                self.expect_regex = regex_can_follow(code).unwrap_or(self.expect_regex);
+            }
+            if self.language == Language::Yaml
+                && matches!(self.state, State::Code)
+                && is_block_scalar_header(code)
+            {
+                self.state = State::BlockScalar {
+                    indent: indentation(line),
+                };
+            }
+        }
+
+        if !stripped {
+            return Some(Cow::Borrowed(line));
+        }
+        if code.trim().is_empty() {
+            return None;
+        }
+        Some(Cow::Owned(code.to_string()))
+    }
+}
+
+fn is_doc_line_comment(syntax: &Syntax, rest: &str) -> bool {
+    syntax.doc_line_comments.iter().any(|prefix| {
+        // `////` is a regular comment
+        rest.starts_with(prefix) && !rest[prefix.len()..].starts_with('/')
+    })
+}
+
+fn is_doc_block_comment(syntax: &Syntax, rest: &str) -> bool {
+    syntax.doc_block_comments.iter().any(|prefix| {
+        // `/**/` and `/***` are regular comments
+        rest.starts_with(prefix) && !rest[prefix.len()..].starts_with(['*', '/'])
+    })
+}
+
+/// Returns the length of a Rust char literal starting at `i`, telling them apart from lifetimes.
+fn rust_literal_len(line: &str, i: usize) -> Option<usize> {
+    let rest = &line[i..];
+    let mut chars = rest.char_indices().skip(1);
+    if !rest.starts_with('\'') {
+        return None;
+    }
+    let (_, first) = chars.next()?;
+    if first == '\\' {
+        let escaped = 2 + rest[2..].chars().next()?.len_utf8();
+        let close = rest[escaped..].find('\'')?;
+        return Some(escaped + close + 1);
+    }
+    let (index, second) = chars.next()?;
+    (second == '\'').then_some(index + 1)
+}
+
+/// Returns the length of a Rust raw string opener like `r#"` at `i`, and the matching closer.
+fn rust_raw_string_open(line: &str, i: usize) -> Option<(usize, String)> {
+    let previous = line[..i].chars().next_back();
+    let prefix_len = if line[i..].starts_with("br") || line[i..].starts_with("cr") {
+        2
+    } else if line[i..].starts_with('r') {
+        1
+    } else {
+        return None;
+    };
+    if previous.is_some_and(|previous| previous.is_alphanumeric() || previous == '_') {
+        return None;
+    }
+    let after_prefix = &line[i + prefix_len..];
+    let hashes = after_prefix.len() - after_prefix.trim_start_matches('#').len();
+    if !after_prefix[hashes..].starts_with('"') {
+        return None;
+    }
+    Some((prefix_len + hashes + 1, format!("\"{}", "#".repeat(hashes))))
+}
+
+/// Keywords after which a `/` starts a regex literal rather than a division.
+const REGEX_KEYWORDS: &[&str] = &[
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+/// Whether a `/` after the code `before` would start a JavaScript regex literal rather than a
+/// division, or `None` if there's no code to tell from.
+fn regex_can_follow(before: &str) -> Option<bool> {
+    let before = before.trim_end();
+    let last = before.chars().next_back()?;
+    if last.is_alphanumeric() || last == '_' || last == '$' {
+        let word_start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .map_or(0, |index| index + 1);
+        return Some(REGEX_KEYWORDS.contains(&&before[word_start..]));
+    }
+    // values end with a closing bracket or a quote, operators and openers with anything else
+    Some(!matches!(last, ')' | ']' | '"' | '\'' | '`'))
+}
+
+/// Returns the length of a JavaScript regex literal starting at the `/` that `rest` starts with,
+/// or `None` if it isn't closed on the same line.
+fn regex_literal_len(rest: &str) -> Option<usize> {
+    if rest.starts_with("//") || rest.starts_with("/*") {
+        return None;
+    }
+    let mut in_class = false;
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => {
+                let flags = rest[index + 1..]
+                    .chars()
+                    .take_while(char::is_ascii_alphabetic)
+                    .count();
+                return Some(index + 1 + flags);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the delimiter of a shell heredoc from the text after its `<<`, and whether leading
+/// tabs are stripped from its lines, as with `<<-`.
+fn heredoc_delimiter(after: &str) -> Option<(String, bool)> {
+    let (after, strip_tabs) = after
+        .strip_prefix('-')
+        .map_or((after, false), |after| (after, true));
+    let after = after.trim_start();
+    let word = after
+        .split(|c: char| c.is_whitespace() || ";|&<>()".contains(c))
+        .next()?;
+    let delimiter = word.replace(['\'', '"', '\\'], "");
+    // leaves out arithmetic shifts like `$((1 << 2))`
+    delimiter
+        .starts_with(|c: char| c.is_alphabetic() || c == '_')
+        .then_some((delimiter, strip_tabs))
+}
+
+fn is_block_scalar_header(code: &str) -> bool {
+    let Some(last_word) = code.split_whitespace().last() else {
+        return false;
+    };
+    last_word.starts_with(['|', '>'])
+        && last_word[1..]
+            .chars()
+            .all(|c| c == '-' || c == '+' || c.is_ascii_digit())
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::number_lines;
+
+    fn strip(language: Language, keep_doc_comments: bool, text: &str) -> Vec<(usize, String)> {
+        Stripper::new(language, keep_doc_comments)
+            .strip(number_lines(text))
+            .into_iter()
+            .map(|line| (line.number.expect("numbered"), line.text.into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn strips_rust_comments_outside_strings() {
+        let text = r##"//! Crate docs
+/// Adds one.
+fn add(x: u32) -> u32 { // trailing
+    /* block /* nested */ still comment */
+    let url = "http://example.com"; // real comment
+    let raw = r#"// not a comment"#;
+    let c = '"'; let s: &'static str = "a";
+    x + 1
+}
+"##;
+        assert_eq!(
+            strip(Language::Rust, true, text),
+            vec![
+                (1, "//! Crate docs".to_string()),
+                (2, "/// Adds one.".to_string()),
+                (3, "fn add(x: u32) -> u32 {".to_string()),
+                (5, r#"    let url = "http://example.com";"#.to_string()),
+                (6, r##"    let raw = r#"// not a comment"#;"##.to_string()),
+                (
+                    7,
+                    r#"    let c = '"'; let s: &'static str = "a";"#.to_string()
+                ),
+                (8, "    x + 1".to_string()),
+                (9, "}".to_string()),
+            ]
+        );
+        let without_docs = strip(Language::Rust, false, text);
+        assert_eq!(without_docs[0], (3, "fn add(x: u32) -> u32 {".to_string()));
+    }
+
+    #[test]
+    fn strips_python_comments_and_optionally_docstrings() {
+        let text = "def f():\n    \"\"\"Docs.\n    More.\"\"\"\n    return '#'  # why\n";
+
+        assert_eq!(
+            strip(Language::Python, false, text),
+            vec![
+                (1, "def f():".to_string()),
+                (4, "    return '#'".to_string()),
+            ]
+        );
+        assert_eq!(strip(Language::Python, true, text).len(), 4);
+    }
+
+    #[test]
+    fn shell_and_yaml_comments_need_word_start() {
+        let shell = "#!/bin/sh\necho $# # count\n";
+        assert_eq!(
+            strip(Language::Shell, false, shell),
+            vec![(1, "#!/bin/sh".to_string()), (2, "echo $#".to_string())]
+        );
+
+        let yaml = "# header\nkey: it's#fine # note\nscript: |\n  # kept\n  echo\nother: 1\n";
+        assert_eq!(
+            strip(Language::Yaml, false, yaml),
+            vec![
+                (2, "key: it's#fine".to_string()),
+                (3, "script: |".to_string()),
+                (4, "  # kept".to_string()),
+                (5, "  echo".to_string()),
+                (6, "other: 1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn javascript_regex_literals_are_kept() {
+        let text = "const url = /https?:\\/\\//i; // scheme\n\
+                    const half = total / 2; // halved\n\
+                    const glob = /[/*]+/g.test(path) ? a : b / c;\n\
+                    return /\\/\\/.*/.exec(line);\n";
+        assert_eq!(
+            strip(Language::JavaScript, false, text),
+            vec![
+                (1, "const url = /https?:\\/\\//i;".to_string()),
+                (2, "const half = total / 2;".to_string()),
+                (
+                    3,
+                    "const glob = /[/*]+/g.test(path) ? a : b / c;".to_string()
+                ),
+                (4, "return /\\/\\/.*/.exec(line);".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn shell_heredoc_bodies_are_kept() {
+        let text = "cat <<'EOF' > notes.md # write notes\n\
+                    # Heading\n\
+                    EOF\n\
+                    cat <<-END\n\
+                    \t# indented\n\
+                    \tEND\n\
+                    echo $((1 << 2)) # shifted\n";
+        assert_eq!(
+            strip(Language::Shell, false, text),
+            vec![
+                (1, "cat <<'EOF' > notes.md".to_string()),
+                (2, "# Heading".to_string()),
+                (3, "EOF".to_string()),
+                (4, "cat <<-END".to_string()),
+                (5, "\t# indented".to_string()),
+                (6, "\tEND".to_string()),
+                (7, "echo $((1 << 2))".to_string()),
+            ]
+        );
+    }
+}
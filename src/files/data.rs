@@ -0,0 +1,400 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::{Line, number_lines};
+
+/// Rows or items of data files kept by default.
+pub const DEFAULT_SAMPLE_ROWS: usize = 5;
+/// Largest share of a data file's size its summary may take, as smaller files read better whole.
+const MAX_SUMMARY_SHARE: f64 = 0.5;
+
+static DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").expect("valid date pattern"));
+static DATETIME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?$")
+        .expect("valid datetime pattern")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DataFile {
+    Csv,
+    Tsv,
+    Json,
+    JsonLines,
+}
+
+impl DataFile {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let data_file = match extension.as_str() {
+            "csv" => Self::Csv,
+            "tsv" => Self::Tsv,
+            "json" => Self::Json,
+            "jsonl" | "ndjson" => Self::JsonLines,
+            _ => return None,
+        };
+        Some(data_file)
+    }
+}
+
+/// Type inferred for a column or field from its values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+enum ValueType {
+    Boolean,
+    Integer,
+    Float,
+    Date,
+    Datetime,
+    String,
+    Array,
+    Object,
+    /// Values of several incompatible types
+    Mixed,
+}
+
+impl ValueType {
+    /// Widens two types to one covering both, or `conflict` if there's none.
+    fn merge(self, other: Self, conflict: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Integer, Self::Float) | (Self::Float, Self::Integer) => Self::Float,
+            (Self::Date, Self::Datetime) | (Self::Datetime, Self::Date) => Self::Datetime,
+            _ => conflict,
+        }
+    }
+
+    fn of_text(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            Self::Boolean
+        } else if value.parse::<i64>().is_ok() {
+            Self::Integer
+        } else if value.parse::<f64>().is_ok() {
+            Self::Float
+        } else if DATE.is_match(value) {
+            Self::Date
+        } else if DATETIME.is_match(value) {
+            Self::Datetime
+        } else {
+            Self::String
+        }
+    }
+
+    fn of_json(value: &Value) -> Option<Self> {
+        let value_type = match value {
+            Value::Null => return None,
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(number) if number.is_f64() => Self::Float,
+            Value::Number(_) => Self::Integer,
+            Value::String(_) => Self::String,
+            Value::Array(_) => Self::Array,
+            Value::Object(_) => Self::Object,
+        };
+        Some(value_type)
+    }
+}
+
+/// Values seen for a column or field.
+#[derive(Debug, Default)]
+struct Column {
+    value_type: Option<ValueType>,
+    /// Whether some values were empty or null.
+    nullable: bool,
+    /// Number of rows or items the field was present in.
+    present: usize,
+}
+
+impl Column {
+    fn observe(&mut self, value_type: Option<ValueType>, conflict: ValueType) {
+        self.present += 1;
+        match (self.value_type, value_type) {
+            (_, None) => self.nullable = true,
+            (None, Some(value_type)) => self.value_type = Some(value_type),
+            (Some(seen), Some(value_type)) => {
+                self.value_type = Some(seen.merge(value_type, conflict))
+            }
+        }
+    }
+
+    fn describe(&self, name: &str, rows: usize) -> String {
+        let value_type = self
+            .value_type
+            .map_or_else(|| "null".to_string(), |value_type| value_type.to_string());
+        let qualifiers = [
+            (self.nullable && self.value_type.is_some()).then_some("nullable"),
+            (self.present < rows).then_some("optional"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        if qualifiers.is_empty() {
+            format!("  {name}: {value_type}")
+        } else {
+            format!("  {name}: {value_type} ({})", qualifiers.join(", "))
+        }
+    }
+}
+
+/// Renders a CSV, TSV or JSON data file as its inferred schema and the first `sample_rows` rows
+/// or items, keeping the line numbers of sampled rows where it can. Returns `None` for files that
+/// aren't data files, that can't be parsed, or whose summary wouldn't be much smaller than
+/// they are.
+pub(super) fn summarize<'a>(
+    path: &Path,
+    text: &'a str,
+    sample_rows: usize,
+) -> Option<Vec<Line<'a>>> {
+    let lines = sample(path, text, sample_rows)?;
+    let size = lines.iter().map(|line| line.text.len() + 1).sum::<usize>();
+    (size as f64 <= text.len() as f64 * MAX_SUMMARY_SHARE).then_some(lines)
+}
+
+/// Renders a data file as its schema and sample, whatever its size, or returns `None` if it has
+/// no more rows than would be sampled.
+fn sample<'a>(path: &Path, text: &'a str, sample_rows: usize) -> Option<Vec<Line<'a>>> {
+    match DataFile::from_path(path)? {
+        DataFile::Csv => summarize_table(text, b',', sample_rows),
+        DataFile::Tsv => summarize_table(text, b'\t', sample_rows),
+        DataFile::Json => summarize_json_array(text, sample_rows),
+        DataFile::JsonLines => summarize_json_lines(text, sample_rows),
+    }
+}
+
+fn summarize_table(text: &str, delimiter: u8, sample_rows: usize) -> Option<Vec<Line<'_>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers().ok()?.clone();
+    let mut columns = headers
+        .iter()
+        .map(|_| Column::default())
+        .collect::<Vec<_>>();
+    let mut rows = 0;
+    // line the first row left out of the sample starts on
+    let mut sample_end = None;
+    for record in reader.records() {
+        let record = record.ok()?;
+        if rows == sample_rows {
+            sample_end = record.position().map(|position| position.line() as usize);
+        }
+        rows += 1;
+        for (column, value) in columns.iter_mut().zip(record.iter()) {
+            let value = value.trim();
+            let value_type = (!value.is_empty()).then(|| ValueType::of_text(value));
+            column.observe(value_type, ValueType::String);
+        }
+    }
+    let sample_end = sample_end?;
+
+    let mut lines = vec![marker(format!(
+        "[table: {rows} rows, {} columns]",
+        columns.len()
+    ))];
+    lines.extend(
+        headers
+            .iter()
+            .zip(&columns)
+            .map(|(name, column)| marker(column.describe(name, rows))),
+    );
+    lines.push(marker(format!(
+        "[header and first {sample_rows} {}]",
+        plural(sample_rows, "row")
+    )));
+    lines.extend(
+        number_lines(text)
+            .into_iter()
+            .take_while(|line| line.number.is_some_and(|number| number < sample_end)),
+    );
+    lines.push(marker(more(rows - sample_rows, "row")));
+    Some(lines)
+}
+
+fn summarize_json_array(text: &str, sample_rows: usize) -> Option<Vec<Line<'_>>> {
+    let Value::Array(items) = serde_json::from_str(text).ok()? else {
+        return None;
+    };
+    if items.len() <= sample_rows {
+        return None;
+    }
+    let mut lines = vec![marker(format!("[json array: {} items]", items.len()))];
+    lines.extend(schema_lines(&items));
+    lines.push(marker(format!(
+        "[first {sample_rows} {}]",
+        plural(sample_rows, "item")
+    )));
+    let sample = serde_json::to_string_pretty(&items[..sample_rows]).ok()?;
+    lines.extend(sample.lines().map(|line| marker(line.to_string())));
+    lines.push(marker(more(items.len() - sample_rows, "item")));
+    Some(lines)
+}
+
+fn summarize_json_lines(text: &str, sample_rows: usize) -> Option<Vec<Line<'_>>> {
+    let numbered = number_lines(text)
+        .into_iter()
+        .filter(|line| !line.text.trim().is_empty())
+        .collect::<Vec<_>>();
+    if numbered.len() <= sample_rows {
+        return None;
+    }
+    let items = numbered
+        .iter()
+        .map(|line| serde_json::from_str(&line.text).ok())
+        .collect::<Option<Vec<Value>>>()?;
+    let mut lines = vec![marker(format!("[json lines: {} items]", items.len()))];
+    lines.extend(schema_lines(&items));
+    lines.push(marker(format!(
+        "[first {sample_rows} {}]",
+        plural(sample_rows, "item")
+    )));
+    lines.extend(numbered.into_iter().take(sample_rows));
+    lines.push(marker(more(items.len() - sample_rows, "item")));
+    Some(lines)
+}
+
+/// Describes the fields of object items, or the type of other items.
+fn schema_lines(items: &[Value]) -> Vec<Line<'static>> {
+    let mut fields = BTreeMap::<&str, Column>::new();
+    let mut elements = Column::default();
+    for item in items {
+        match item {
+            Value::Object(object) => {
+                for (name, value) in object {
+                    fields
+                        .entry(name)
+                        .or_default()
+                        .observe(ValueType::of_json(value), ValueType::Mixed);
+                }
+            }
+            item => elements.observe(ValueType::of_json(item), ValueType::Mixed),
+        }
+    }
+    let mut lines = fields
+        .iter()
+        .map(|(name, field)| marker(field.describe(name, items.len())))
+        .collect::<Vec<_>>();
+    if elements.present > 0 {
+        lines.push(marker(
+            elements.describe("[non-object items]", elements.present),
+        ));
+    }
+    lines
+}
+
+const fn marker(text: String) -> Line<'static> {
+    Line {
+        number: None,
+        text: Cow::Owned(text),
+    }
+}
+
+fn more(count: usize, noun: &str) -> String {
+    format!("... ({count} more {})", plural(count, noun))
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        noun.to_string()
+    } else {
+        format!("{noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{LineNumberStyle, annotate_line_numbers};
+
+    fn render(path: &str, text: &str, sample_rows: usize) -> Option<String> {
+        sample(Path::new(path), text, sample_rows)
+            .map(|lines| annotate_line_numbers(&lines, LineNumberStyle::Plain))
+    }
+
+    #[test]
+    fn only_large_data_files_are_summarized() {
+        let rows = |count: usize| {
+            (1..=count)
+                .map(|i| format!("{i},item {i},{}.5\n", i * 3))
+                .collect::<String>()
+        };
+        let small = format!("id,name,price\n{}", rows(6));
+        assert_eq!(summarize(Path::new("small.csv"), &small, 5), None);
+        let large = format!("id,name,price\n{}", rows(1000));
+        let summary = summarize(Path::new("large.csv"), &large, 5).expect("large table");
+        assert_eq!(summary.len(), 12);
+
+        let config = r#"["alpha", "beta", "gamma", "delta", "epsilon", "zeta"]"#;
+        assert_eq!(summarize(Path::new("config.json"), config, 5), None);
+    }
+
+    #[test]
+    fn tables_are_rendered_as_schema_and_sample() {
+        let csv = "id,name,price,added\n\
+                   1,apple,0.5,2024-01-02\n\
+                   2,\"pear,\nconference\",,2024-01-03\n\
+                   3,plum,2,2024-01-04T10:00:00Z\n\
+                   4,fig,1.25,2024-01-05\n";
+        assert_eq!(
+            render("fruit.csv", csv, 2).expect("csv with more rows than sampled"),
+            "  [table: 4 rows, 4 columns]\n    \
+               id: integer\n    \
+               name: string\n    \
+               price: float (nullable)\n    \
+               added: datetime\n  \
+             [header and first 2 rows]\n\
+             1 id,name,price,added\n\
+             2 1,apple,0.5,2024-01-02\n\
+             3 2,\"pear,\n\
+             4 conference\",,2024-01-03\n  \
+             ... (2 more rows)\n"
+        );
+        assert_eq!(render("fruit.csv", csv, 4), None);
+
+        let tsv = "a\tb\nx\t1\ny\t2\n";
+        assert!(
+            render("data.tsv", tsv, 1)
+                .expect("tsv with more rows than sampled")
+                .contains("b: integer")
+        );
+    }
+
+    #[test]
+    fn json_arrays_and_lines_are_rendered_as_schema_and_sample() {
+        let json =
+            r#"[{"id": 1, "tags": ["a"]}, {"id": 2.5, "note": null}, {"id": 3, "note": "x"}]"#;
+        assert_eq!(
+            render("items.json", json, 1).expect("array with more items than sampled"),
+            "[json array: 3 items]\n  \
+             id: float\n  \
+             note: string (nullable, optional)\n  \
+             tags: array (optional)\n\
+             [first 1 item]\n\
+             [\n  \
+               {\n    \
+                 \"id\": 1,\n    \
+                 \"tags\": [\n      \
+                   \"a\"\n    \
+                 ]\n  \
+               }\n\
+             ]\n\
+             ... (2 more items)\n"
+        );
+        assert_eq!(render("config.json", r#"{"a": 1}"#, 1), None);
+
+        let jsonl = "{\"id\": 1}\n\n{\"id\": \"two\"}\n{\"id\": 3}\n";
+        assert_eq!(
+            render("events.jsonl", jsonl, 2).expect("more lines than sampled"),
+            "  [json lines: 3 items]\n    \
+               id: mixed\n  \
+             [first 2 items]\n\
+             1 {\"id\": 1}\n\
+             3 {\"id\": \"two\"}\n  \
+             ... (1 more item)\n"
+        );
+    }
+}
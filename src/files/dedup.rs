@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use super::Duplicate;
+
+/// Words per shingle hashed into a file's fingerprint.
+const SHINGLE_WORDS: usize = 3;
+/// Files with fewer shingles than this are too short to fingerprint reliably.
+const MIN_SHINGLES: usize = 16;
+
+/// Finds files duplicating an earlier file in `contents`, which should be ordered by path, along
+/// with the file each duplicates.
+///
+/// Identical contents are always matched. With a `near_threshold`, files whose fingerprints are
+/// at least that similar are matched too, using a 64-bit SimHash over word shingles.
+pub(super) fn find_duplicates(
+    contents: &[(&Path, &str)],
+    near_threshold: Option<f64>,
+) -> Vec<(PathBuf, Duplicate)> {
+    let mut duplicates = vec![];
+    let mut identical = HashMap::<&str, &Path>::new();
+    let mut fingerprints = Vec::<(&Path, u64)>::new();
+    for &(path, content) in contents {
+        if content.is_empty() {
+            continue;
+        }
+        if let Some(&of) = identical.get(content) {
+            duplicates.push((path.to_path_buf(), Duplicate::of(of, None)));
+            continue;
+        }
+        identical.insert(content, path);
+
+        let Some(threshold) = near_threshold else {
+            continue;
+        };
+        let Some(fingerprint) = simhash(content) else {
+            continue;
+        };
+        let nearest = fingerprints
+            .iter()
+            .map(|&(of, other)| (of, similarity(fingerprint, other)))
+            .filter(|&(_, similarity)| similarity >= threshold)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        match nearest {
+            Some((of, similarity)) => {
+                let percent = (similarity * 100.0).round() as u8;
+                duplicates.push((path.to_path_buf(), Duplicate::of(of, Some(percent))));
+            }
+            None => fingerprints.push((path, fingerprint)),
+        }
+    }
+    duplicates
+}
+
+/// Fingerprints content so that similar content gets fingerprints differing in few bits.
+///
+/// Numbers are skipped, so that line numbers shifted by an insertion don't count as changes.
+fn simhash(content: &str) -> Option<u64> {
+    let words = content
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>();
+    let shingles = words.windows(SHINGLE_WORDS);
+    if shingles.len() < MIN_SHINGLES {
+        return None;
+    }
+    let mut weights = [0i64; 64];
+    for shingle in shingles {
+        let mut hasher = DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        let hash = hasher.finish();
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .fold(0, |fingerprint, (bit, _)| fingerprint | 1 << bit),
+    )
+}
+
+fn similarity(a: u64, b: u64) -> f64 {
+    1.0 - f64::from((a ^ b).count_ones()) / 64.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_and_near_identical_duplicates_are_found() {
+        let config = (1..=40)
+            .map(|i| format!("setting_{i} = value_{i}\n"))
+            .collect::<String>();
+        let tweaked = config.replace("value_7\n", "changed\n");
+        let unrelated = (1..=40)
+            .map(|i| format!("fn handler_{i}() -> Result<Response> {{ todo!() }}\n"))
+            .collect::<String>();
+        let contents = [
+            (Path::new("a/config.toml"), config.as_str()),
+            (Path::new("b/config.toml"), config.as_str()),
+            (Path::new("c/config.toml"), tweaked.as_str()),
+            (Path::new("d/handlers.rs"), unrelated.as_str()),
+        ];
+
+        assert_eq!(
+            find_duplicates(&contents, None),
+            vec![(
+                PathBuf::from("b/config.toml"),
+                Duplicate::of(Path::new("a/config.toml"), None)
+            )]
+        );
+
+        let near = find_duplicates(&contents, Some(0.85));
+        assert_eq!(near.len(), 2);
+        let (path, duplicate) = &near[1];
+        assert_eq!(path, Path::new("c/config.toml"));
+        assert_eq!(duplicate.of, Path::new("a/config.toml"));
+        assert!(
+            duplicate
+                .similarity
+                .is_some_and(|similarity| similarity >= 85)
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use image::ImageReader;
+use rusqlite::{Connection, OpenFlags};
+use tracing::warn;
+
+/// Archive entries listed before the rest are elided.
+const MAX_LISTED_ENTRIES: usize = 100;
+
+/// A binary file to describe.
+pub(super) struct Binary<'a> {
+    pub path: &'a Path,
+    /// MIME type detected from the file's magic bytes.
+    pub mime: Option<&'static str>,
+    pub size: u64,
+}
+
+/// Gives a short description of some kind of binary file, to include in place of its content.
+trait Describer: Sync {
+    /// Describes the file, or returns `None` if it isn't of a kind this describer handles.
+    fn describe(&self, binary: &Binary) -> Result<Option<String>>;
+}
+
+/// Describers in the order they are tried, ending with one that describes any file.
+static DESCRIBERS: &[&dyn Describer] = &[&Sqlite, &Image, &Zip, &Tar, &Generic];
+
+/// Describes a binary file with the first describer that handles it.
+pub(super) fn describe(binary: &Binary) -> String {
+    DESCRIBERS
+        .iter()
+        .find_map(|describer| {
+            describer
+                .describe(binary)
+                .inspect_err(|err| warn!("Failed to describe {}: {err}", binary.path.display()))
+                .ok()
+                .flatten()
+        })
+        .unwrap_or_else(|| Generic::description(binary))
+}
+
+/// The `CREATE` statements of a SQLite database's tables, indexes, views and triggers.
+struct Sqlite;
+
+impl Describer for Sqlite {
+    fn describe(&self, binary: &Binary) -> Result<Option<String>> {
+        if binary.mime != Some("application/vnd.sqlite3") {
+            return Ok(None);
+        }
+        let connection = Connection::open_with_flags(
+            binary.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        let mut statement = connection
+            .prepare("SELECT sql FROM sqlite_schema WHERE sql IS NOT NULL ORDER BY rowid")?;
+        let mut description = format!("SQLite database, {}\n", human_size(binary.size));
+        for sql in statement.query_map([], |row| row.get::<_, String>(0))? {
+            let _ = writeln!(description, "{};", sql?);
+        }
+        Ok(Some(description))
+    }
+}
+
+/// The format and dimensions of an image.
+struct Image;
+
+impl Describer for Image {
+    fn describe(&self, binary: &Binary) -> Result<Option<String>> {
+        if !binary.mime.is_some_and(|mime| mime.starts_with("image/")) {
+            return Ok(None);
+        }
+        let reader = ImageReader::open(binary.path)?.with_guessed_format()?;
+        let Some(format) = reader.format() else {
+            return Ok(None);
+        };
+        let (width, height) = reader.into_dimensions()?;
+        let name = format.extensions_str().first().map_or_else(
+            || format!("{format:?}"),
+            |extension| extension.to_uppercase(),
+        );
+        Ok(Some(format!(
+            "{name} image, {width}x{height}, {}\n",
+            human_size(binary.size)
+        )))
+    }
+}
+
+/// The files in a zip archive.
+struct Zip;
+
+impl Describer for Zip {
+    fn describe(&self, binary: &Binary) -> Result<Option<String>> {
+        if binary.mime != Some("application/zip") {
+            return Ok(None);
+        }
+        let mut archive = zip::ZipArchive::new(BufReader::new(File::open(binary.path)?))?;
+        let entries = (0..archive.len())
+            .map(|index| {
+                let entry = archive.by_index_raw(index)?;
+                Ok((entry.name().to_string(), entry.size()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(listing("zip archive", binary.size, entries)))
+    }
+}
+
+/// The files in a tar archive, optionally gzipped.
+struct Tar;
+
+impl Describer for Tar {
+    fn describe(&self, binary: &Binary) -> Result<Option<String>> {
+        let file = BufReader::new(File::open(binary.path)?);
+        let (kind, reader): (_, Box<dyn Read>) = match binary.mime {
+            Some("application/x-tar") => ("tar archive", Box::new(file)),
+            Some("application/gzip") if is_tarball(binary.path) => {
+                ("gzipped tar archive", Box::new(GzDecoder::new(file)))
+            }
+            _ => return Ok(None),
+        };
+        let mut archive = tar::Archive::new(reader);
+        let entries = archive
+            .entries()?
+            .map(|entry| {
+                let entry = entry?;
+                Ok((entry.path()?.display().to_string(), entry.size()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(listing(kind, binary.size, entries)))
+    }
+}
+
+fn is_tarball(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".tar.gz") || name.ends_with(".tgz"))
+}
+
+/// The MIME type and size of any file.
+struct Generic;
+
+impl Generic {
+    fn description(binary: &Binary) -> String {
+        let mime = binary.mime.unwrap_or("unknown type");
+        format!("{mime}, {}\n", human_size(binary.size))
+    }
+}
+
+impl Describer for Generic {
+    fn describe(&self, binary: &Binary) -> Result<Option<String>> {
+        Ok(Some(Self::description(binary)))
+    }
+}
+
+fn listing(kind: &str, size: u64, entries: Vec<(String, u64)>) -> String {
+    let count = match entries.len() {
+        1 => "1 entry".to_string(),
+        count => format!("{count} entries"),
+    };
+    let mut description = format!("{kind}, {}, {count}\n", human_size(size));
+    let more = entries.len().saturating_sub(MAX_LISTED_ENTRIES);
+    for (name, size) in entries.into_iter().take(MAX_LISTED_ENTRIES) {
+        let _ = writeln!(description, "{name} ({})", human_size(size));
+    }
+    if more > 0 {
+        let _ = writeln!(description, "... ({more} more entries)");
+    }
+    description
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn binary(path: &Path) -> Binary<'_> {
+        let mut sample = vec![];
+        File::open(path)
+            .expect("should open file")
+            .read_to_end(&mut sample)
+            .expect("should read file");
+        Binary {
+            path,
+            mime: infer::get(&sample).map(|kind| kind.mime_type()),
+            size: sample.len() as u64,
+        }
+    }
+
+    #[test]
+    fn databases_and_archives_are_described() -> Result<()> {
+        let dir = TempDir::new();
+
+        let database = dir.path.join("app.db");
+        let connection = Connection::open(&database)?;
+        connection.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+             CREATE INDEX users_name ON users (name);",
+        )?;
+        drop(connection);
+        let description = describe(&binary(&database));
+        assert!(description.starts_with("SQLite database, "));
+        assert!(description.ends_with(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);\n\
+             CREATE INDEX users_name ON users (name);\n"
+        ));
+
+        let archive = dir.path.join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&archive)?);
+        writer.start_file("README.md", SimpleFileOptions::default())?;
+        writer.write_all(b"hello")?;
+        writer.finish()?;
+        let description = describe(&binary(&archive));
+        assert!(description.ends_with(", 1 entry\nREADME.md (5 B)\n"));
+
+        let unknown = dir.path.join("blob.bin");
+        std::fs::write(&unknown, [0u8; 2048])?;
+        assert_eq!(describe(&binary(&unknown)), "unknown type, 2.0 KiB\n");
+
+        Ok(())
+    }
+}
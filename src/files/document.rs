@@ -0,0 +1,170 @@
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use roxmltree::{Document, Node};
+
+const WORDPROCESSINGML_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const OPENDOCUMENT_TEXT_NAMESPACE: &str = "urn:oasis:names:tc:opendocument:xmlns:text:1.0";
+/// Largest uncompressed XML read from a document, which bounds zip bombs posing as documents.
+const MAX_XML_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Document formats whose plain text can be extracted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DocumentKind {
+    Docx,
+    Odt,
+    Pdf,
+}
+
+impl DocumentKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let kind = match extension.as_str() {
+            "docx" => Self::Docx,
+            "odt" => Self::Odt,
+            "pdf" => Self::Pdf,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Extracts the plain text of a document, a line per paragraph.
+    pub fn extract(self, bytes: &[u8]) -> Result<String> {
+        match self {
+            Self::Docx => {
+                let xml = zip_entry(bytes, "word/document.xml", MAX_XML_BYTES)?;
+                xml_paragraphs(&xml, WORDPROCESSINGML_NAMESPACE, &["p"])
+            }
+            Self::Odt => {
+                let xml = zip_entry(bytes, "content.xml", MAX_XML_BYTES)?;
+                xml_paragraphs(&xml, OPENDOCUMENT_TEXT_NAMESPACE, &["p", "h"])
+            }
+            // the PDF parser panics on many malformed files rather than returning an error
+            Self::Pdf => std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes))
+                .map_err(|_| anyhow!("PDF parser panicked"))?
+                .map_err(Into::into),
+        }
+    }
+}
+
+fn zip_entry(bytes: &[u8], name: &str, limit: u64) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let entry = archive
+        .by_name(name)
+        .with_context(|| format!("Missing {name} in document"))?;
+    let mut xml = String::new();
+    entry.take(limit + 1).read_to_string(&mut xml)?;
+    if xml.len() as u64 > limit {
+        bail!("{name} is over {limit} bytes");
+    }
+    Ok(xml)
+}
+
+/// Collects the text of paragraph elements, ignoring paragraphs nested in others.
+fn xml_paragraphs(xml: &str, namespace: &str, paragraphs: &[&str]) -> Result<String> {
+    let document = Document::parse(xml)?;
+    let is_paragraph = |node: &Node| {
+        node.tag_name().namespace() == Some(namespace)
+            && paragraphs.contains(&node.tag_name().name())
+    };
+    let mut text = String::new();
+    for paragraph in document.descendants().filter(is_paragraph) {
+        if paragraph
+            .ancestors()
+            .skip(1)
+            .any(|node| is_paragraph(&node))
+        {
+            continue;
+        }
+        push_paragraph_text(&mut text, paragraph, namespace);
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+fn push_paragraph_text(text: &mut String, node: Node, namespace: &str) {
+    for child in node.children() {
+        if child.is_text() {
+            // WordprocessingML keeps text in <w:t> runs, so whitespace between its elements is
+            // formatting rather than content
+            if namespace != WORDPROCESSINGML_NAMESPACE || node.tag_name().name() == "t" {
+                text.push_str(child.text().unwrap_or_default());
+            }
+            continue;
+        }
+        if child.tag_name().namespace() != Some(namespace) {
+            push_paragraph_text(text, child, namespace);
+            continue;
+        }
+        match child.tag_name().name() {
+            "tab" => text.push('\t'),
+            "br" | "cr" | "line-break" => text.push('\n'),
+            "s" => {
+                // <text:s text:c="3"/> stands for a run of spaces
+                let count = child
+                    .attribute((OPENDOCUMENT_TEXT_NAMESPACE, "c"))
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or(1);
+                text.push_str(&" ".repeat(count));
+            }
+            _ => push_paragraph_text(text, child, namespace),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    fn zipped(name: &str, contents: &str) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .expect("should start zip entry");
+        writer
+            .write_all(contents.as_bytes())
+            .expect("should write zip entry");
+        writer.finish().expect("should finish zip").into_inner()
+    }
+
+    #[test]
+    fn docx_and_odt_paragraphs_are_extracted() -> Result<()> {
+        let docx = zipped(
+            "word/document.xml",
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:r><w:t>Design</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve"> doc</w:t></w:r></w:p>
+    <w:p><w:r><w:t>Second</w:t></w:r></w:p>
+  </w:body>
+</w:document>"#,
+        );
+        assert_eq!(DocumentKind::Docx.extract(&docx)?, "Design\t doc\nSecond\n");
+
+        let odt = zipped(
+            "content.xml",
+            r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+  <office:body><office:text>
+    <text:h>Title</text:h>
+    <text:p>a<text:s text:c="2"/>b<text:span>c</text:span></text:p>
+  </office:text></office:body>
+</office:document-content>"#,
+        );
+        assert_eq!(DocumentKind::Odt.extract(&odt)?, "Title\na  bc\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_and_malformed_documents_fail_to_extract() {
+        let docx = zipped("word/document.xml", &"<w:p/>".repeat(100));
+        assert!(zip_entry(&docx, "word/document.xml", 64).is_err());
+        assert!(zip_entry(&docx, "word/document.xml", 600).is_ok());
+        assert!(DocumentKind::Pdf.extract(b"%PDF-1.7\n%%EOF").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::borrow::Cow;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use serde::{Deserialize, Serialize};
+
+/// Share of UTF-16 code units that need a zero byte for text without a byte order mark to be
+/// taken as UTF-16, as ASCII-heavy text is.
+const UTF16_MIN_ZERO_SHARE: f64 = 0.4;
+/// Share of UTF-16 code units that may have a zero byte in the other half.
+const UTF16_MAX_OTHER_ZERO_SHARE: f64 = 0.05;
+/// Valid multi-byte UTF-8 sequences needed per invalid byte for text to be taken as UTF-8 with a
+/// few stray bytes, rather than as a legacy encoding.
+const UTF8_VALID_PER_INVALID: usize = 10;
+
+/// How sure encoding detection is of the encoding content was decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Confidence {
+    /// From a byte order mark or `--encoding`, or valid UTF-8.
+    Certain,
+    /// Guessed from byte patterns that fit the encoding far better than others.
+    High,
+    /// A guess that may well be wrong.
+    Low,
+}
+
+/// Guesses the encoding of UTF-16 text without a byte order mark from where its zero bytes are.
+pub(super) fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let units = bytes.len() / 2;
+    if units == 0 {
+        return None;
+    }
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for unit in bytes.chunks_exact(2) {
+        even_zeros += usize::from(unit[0] == 0);
+        odd_zeros += usize::from(unit[1] == 0);
+    }
+    let share = |zeros: usize| zeros as f64 / units as f64;
+    if share(odd_zeros) >= UTF16_MIN_ZERO_SHARE && share(even_zeros) <= UTF16_MAX_OTHER_ZERO_SHARE {
+        Some(UTF_16LE)
+    } else if share(even_zeros) >= UTF16_MIN_ZERO_SHARE
+        && share(odd_zeros) <= UTF16_MAX_OTHER_ZERO_SHARE
+    {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Detects the encoding of a file's bytes, and how sure the detection is.
+///
+/// A byte order mark always wins, then the `fallback` chosen by the user. Otherwise UTF-16 is
+/// recognized from its zero bytes, UTF-8 with only a few invalid bytes is taken as UTF-8, and
+/// anything else that isn't valid UTF-8 is guessed from its byte patterns, e.g. as Shift-JIS,
+/// GBK, KOI8-R or Windows-1252.
+pub(super) fn detect(
+    bytes: &[u8],
+    fallback: Option<&'static Encoding>,
+) -> (&'static Encoding, Confidence) {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return (encoding, Confidence::Certain);
+    }
+    if let Some(encoding) = fallback {
+        return (encoding, Confidence::Certain);
+    }
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return (encoding, Confidence::High);
+    }
+    let (valid, invalid) = utf8_sequences(bytes);
+    if invalid == 0 {
+        return (UTF_8, Confidence::Certain);
+    }
+    if valid >= invalid * UTF8_VALID_PER_INVALID {
+        return (UTF_8, Confidence::High);
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let (encoding, assured) = detector.guess_assess(None, false);
+    let confidence = if assured {
+        Confidence::High
+    } else {
+        Confidence::Low
+    };
+    (encoding, confidence)
+}
+
+/// Counts the valid multi-byte UTF-8 sequences in `bytes` and the invalid ones.
+fn utf8_sequences(mut bytes: &[u8]) -> (usize, usize) {
+    let count_multibyte = |text: &str| text.chars().filter(|c| !c.is_ascii()).count();
+    let (mut valid, mut invalid) = (0, 0);
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => return (valid + count_multibyte(text), invalid),
+            Err(err) => {
+                let (text, rest) = bytes.split_at(err.valid_up_to());
+                valid += count_multibyte(std::str::from_utf8(text).unwrap_or_default());
+                invalid += 1;
+                // an incomplete sequence at the end has no length
+                bytes = &rest[err.error_len().unwrap_or(rest.len())..];
+            }
+        }
+    }
+}
+
+/// Decodes a file's bytes with the detected encoding, dropping any byte order mark and
+/// normalizing CRLF line endings to LF.
+pub(super) fn decode<'a>(
+    bytes: &'a [u8],
+    fallback: Option<&'static Encoding>,
+) -> (Cow<'a, str>, &'static Encoding, Confidence) {
+    let (encoding, confidence) = detect(bytes, fallback);
+    let (text, encoding, _) = encoding.decode(bytes);
+    let text = if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        text
+    };
+    (text, encoding, confidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{KOI8_R, SHIFT_JIS, WINDOWS_1252};
+
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn utf16_and_legacy_encodings_are_decoded() {
+        let mut with_bom = vec![0xFF, 0xFE];
+        with_bom.extend(utf16le("caf\u{e9}\r\n"));
+        assert_eq!(
+            decode(&with_bom, None),
+            (Cow::from("caf\u{e9}\n"), UTF_16LE, Confidence::Certain)
+        );
+
+        let without_bom = utf16le("REGEDIT4\r\n[HKEY_CURRENT_USER]\r\n");
+        assert_eq!(
+            decode(&without_bom, None),
+            (
+                Cow::from("REGEDIT4\n[HKEY_CURRENT_USER]\n"),
+                UTF_16LE,
+                Confidence::High
+            )
+        );
+
+        let (text, encoding, _) = decode(b"caf\xe9", None);
+        assert_eq!((text, encoding), (Cow::from("caf\u{e9}"), WINDOWS_1252));
+        assert_eq!(
+            decode("café".as_bytes(), None),
+            (Cow::from("café"), UTF_8, Confidence::Certain)
+        );
+    }
+
+    #[test]
+    fn legacy_encodings_and_stray_bytes_are_detected() {
+        let (shift_jis, _, _) =
+            SHIFT_JIS.encode("これは日本語のテキストです。文字コードを判定します。");
+        let (text, encoding, _) = decode(&shift_jis, None);
+        assert_eq!(encoding, SHIFT_JIS);
+        assert_eq!(text, "これは日本語のテキストです。文字コードを判定します。");
+
+        let russian = "Это русский текст, который нужно правильно распознать.";
+        let (koi8, _, _) = KOI8_R.encode(russian);
+        // KOI8-U is a superset of KOI8-R
+        assert_eq!(decode(&koi8, None).0, russian);
+
+        // one stray byte in otherwise UTF-8 text doesn't turn the rest into mojibake
+        let mut stray = "naïve café résumé déjà vu ".repeat(4).into_bytes();
+        stray.push(0xff);
+        let (text, encoding, confidence) = decode(&stray, None);
+        assert_eq!((encoding, confidence), (UTF_8, Confidence::High));
+        assert!(text.starts_with("naïve café"));
+    }
+}
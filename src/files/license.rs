@@ -0,0 +1,354 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
+
+use super::{Line, ReadOptions, binary, encoding, number_lines};
+use crate::discovery::DiscoveredFile;
+
+/// Header blocks shared by more files than this are taken as license boilerplate by default.
+pub const DEFAULT_REPEATS: usize = 2;
+/// Bytes read from the start of each file when looking for repeated header blocks.
+const SCAN_BYTES: usize = 16 * 1024;
+const SPDX_TAG: &str = "SPDX-License-Identifier:";
+
+/// Block comment delimiters a header can be written in.
+const BLOCK_COMMENTS: &[(&str, &str)] = &[("/*", "*/"), ("<!--", "-->")];
+/// Comment markers stripped from header lines before comparing them, longest first.
+const MARKERS: &[&str] = &[
+    "/**", "/*", "*/", "//!", "///", "//", "<!--", "-->", "##", "#", "--", ";;", "*",
+];
+
+/// Licenses recognized from phrases of their usual header text, checked in order.
+const KNOWN_LICENSES: &[(&str, &[&str])] = &[
+    (
+        "Apache-2.0",
+        &["licensed under the apache license, version 2.0"],
+    ),
+    ("MIT", &["permission is hereby granted, free of charge"]),
+    (
+        "BSD-3-Clause",
+        &[
+            "redistribution and use in source and binary forms",
+            "neither the name",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["redistribution and use in source and binary forms"],
+    ),
+    ("AGPL-3.0", &["gnu affero general public license"]),
+    (
+        "LGPL-3.0",
+        &["gnu lesser general public license", "version 3"],
+    ),
+    ("LGPL-2.1", &["gnu lesser general public license"]),
+    ("GPL-3.0", &["gnu general public license", "version 3"]),
+    ("GPL-2.0", &["gnu general public license", "version 2"]),
+    ("MPL-2.0", &["mozilla public license, v. 2.0"]),
+    (
+        "ISC",
+        &["permission to use, copy, modify, and/or distribute this software for any purpose"],
+    ),
+];
+
+/// License headers found at the top of a project's files.
+#[derive(Debug, Default)]
+pub(super) struct Licenses {
+    /// Unrecognized license headers shared by enough files, by their comparison key, along with
+    /// the `LicenseRef-N` name they are shown as.
+    repeated: HashMap<String, String>,
+}
+
+/// A license header found at the top of a file.
+#[derive(Debug)]
+pub(super) struct LicenseHeader {
+    /// SPDX identifier of the license, or a `LicenseRef-N` name for unrecognized ones.
+    pub license: String,
+    /// The header as written, comment markers included.
+    pub text: String,
+    lines: Range<usize>,
+}
+
+impl LicenseHeader {
+    /// Replaces the header with a single line naming its license.
+    pub fn replace<'a>(&self, mut lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        let marker = Line {
+            number: None,
+            text: Cow::Owned(format!("[license header: {}]", self.license)),
+        };
+        lines.splice(self.lines.clone(), [marker]);
+        lines
+    }
+}
+
+impl Licenses {
+    /// Whether the start of a discovered file is scanned for a repeated header block, which is
+    /// only done for files that would be read. Sensitive and test files aren't even opened.
+    pub fn scans(file: &DiscoveredFile, options: &ReadOptions) -> bool {
+        !file.excluded
+            && file.pruned.is_none()
+            && file.class.is_none()
+            && options.denies_sensitive(&file.path).is_none()
+            && options.skips_test_file(&file.path).is_none()
+    }
+
+    /// Takes the unrecognized header blocks mentioning a copyright or license that are shared by
+    /// more than `repeats` files as license headers, given the [`scan_header`] result of each
+    /// scanned file in path order.
+    pub fn from_headers(headers: impl IntoIterator<Item = Option<String>>, repeats: usize) -> Self {
+        let mut counts = HashMap::<String, usize>::new();
+        let mut order = vec![];
+        for key in headers.into_iter().flatten() {
+            let count = counts.entry(key.clone()).or_default();
+            if *count == 0 {
+                order.push(key);
+            }
+            *count += 1;
+        }
+        let repeated = order
+            .into_iter()
+            .filter(|key| counts[key] > repeats)
+            .enumerate()
+            .map(|(index, key)| (key, format!("LicenseRef-{}", index + 1)))
+            .collect();
+        Self { repeated }
+    }
+
+    /// Finds the license header a file starts with, if it has one.
+    pub fn find(&self, lines: &[Line]) -> Option<LicenseHeader> {
+        let range = header_block(lines)?;
+        let header = &lines[range.clone()];
+        let words = words(header);
+        let license = identify(header, &words)
+            .or_else(|| self.repeated.get(&repetition_key(&words)).cloned())?;
+        let text = header
+            .iter()
+            .map(|line| line.text.as_ref())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(LicenseHeader {
+            license,
+            text,
+            lines: range,
+        })
+    }
+}
+
+/// Reads the header block at the start of a text file, returning its comparison key if it's an
+/// unrecognized license header.
+pub fn scan_header(path: &Path, options: &ReadOptions) -> Option<String> {
+    let mut prefix = Vec::with_capacity(SCAN_BYTES);
+    File::open(path)
+        .ok()?
+        .take(SCAN_BYTES as u64)
+        .read_to_end(&mut prefix)
+        .ok()?;
+    if binary::judge_sample(path, &prefix, &options.binary_detection).binary {
+        return None;
+    }
+    let (text, ..) = encoding::decode(&prefix, options.encoding);
+    let lines = number_lines(&text);
+    let range = header_block(&lines)?;
+    // a block running to the end of a full sample may be cut off
+    if range.end == lines.len() && prefix.len() == SCAN_BYTES {
+        return None;
+    }
+    let header = &lines[range];
+    let words = words(header);
+    let mentions_license = words.contains("copyright") || words.contains("licen");
+    (mentions_license && identify(header, &words).is_none()).then(|| repetition_key(&words))
+}
+
+/// Index range of the comment lines a file starts with, after any shebang line.
+fn header_block(lines: &[Line]) -> Option<Range<usize>> {
+    let first = lines.first()?;
+    let start = usize::from(first.text.starts_with("#!") && !first.text.starts_with("#!["));
+    let mut end = start;
+    let mut closing = None;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        // only the file's own lines, not excerpts or converted content
+        if line.number != Some(index + 1) {
+            break;
+        }
+        let trimmed = line.text.trim();
+        if let Some(close) = closing {
+            if trimmed.contains(close) {
+                closing = None;
+            }
+        } else if let Some((open, close)) = BLOCK_COMMENTS
+            .iter()
+            .find(|(open, _)| trimmed.starts_with(open))
+        {
+            if !trimmed[open.len()..].contains(close) {
+                closing = Some(*close);
+            }
+        } else if !is_line_comment(trimmed) {
+            break;
+        }
+        end = index + 1;
+    }
+    (closing.is_none() && end > start).then_some(start..end)
+}
+
+fn is_line_comment(line: &str) -> bool {
+    // `#` only with a space after it, which leaves out `#include` and `#[attribute]`
+    ["//", "-- ", ";;", "# ", "##"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+        || line == "#"
+        || line == "--"
+}
+
+/// The lowercased words of header lines, without comment markers.
+fn words(header: &[Line]) -> String {
+    header
+        .iter()
+        .map(|line| {
+            let text = line.text.trim();
+            let text = MARKERS
+                .iter()
+                .find_map(|marker| text.strip_prefix(marker))
+                .unwrap_or(text);
+            let text = text
+                .strip_suffix("*/")
+                .or_else(|| text.strip_suffix("-->"))
+                .unwrap_or(text);
+            text.to_lowercase()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Compares header blocks without their digits, so that differing copyright years still match.
+fn repetition_key(words: &str) -> String {
+    words.chars().filter(|c| !c.is_ascii_digit()).collect()
+}
+
+/// Identifies a header's license from an SPDX tag or the phrases of a known license.
+fn identify(header: &[Line], words: &str) -> Option<String> {
+    let spdx = header.iter().find_map(|line| {
+        let (_, expression) = line.text.split_once(SPDX_TAG)?;
+        let expression = expression.trim();
+        let expression = expression
+            .strip_suffix("*/")
+            .or_else(|| expression.strip_suffix("-->"))
+            .unwrap_or(expression)
+            .trim();
+        (!expression.is_empty()).then(|| expression.to_string())
+    });
+    spdx.or_else(|| {
+        KNOWN_LICENSES
+            .iter()
+            .find(|(_, phrases)| phrases.iter().all(|phrase| words.contains(phrase)))
+            .map(|(license, _)| license.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{LineNumberStyle, annotate_line_numbers};
+
+    fn strip(licenses: &Licenses, text: &str) -> Option<String> {
+        let lines = number_lines(text);
+        let header = licenses.find(&lines)?;
+        Some(annotate_line_numbers(
+            &header.replace(lines),
+            LineNumberStyle::Plain,
+        ))
+    }
+
+    #[test]
+    fn known_and_repeated_license_headers_are_replaced() {
+        let licenses = Licenses::default();
+        let apache = "#!/usr/bin/env python\n\
+                      # Copyright 2024 Acme\n\
+                      #\n\
+                      # Licensed under the Apache License, Version 2.0 (the \"License\");\n\
+                      # you may not use this file except in compliance with the License.\n\
+                      \n\
+                      import os\n";
+        assert_eq!(
+            strip(&licenses, apache).expect("apache header"),
+            "1 #!/usr/bin/env python\n  \
+             [license header: Apache-2.0]\n\
+             6 \n\
+             7 import os\n"
+        );
+
+        let spdx = "/*\n * SPDX-License-Identifier: MIT OR Apache-2.0\n */\nint x;\n";
+        assert!(
+            strip(&licenses, spdx)
+                .expect("spdx header")
+                .contains("[license header: MIT OR Apache-2.0]")
+        );
+
+        let custom = "// Copyright 2023 Acme Corp. All rights reserved.\n\
+                      // Internal use only.\n";
+        let attribute = "#![allow(dead_code)]\n// Copyright Acme\n";
+        assert_eq!(strip(&licenses, custom), None);
+        assert_eq!(strip(&licenses, attribute), None);
+
+        let licenses = Licenses {
+            repeated: HashMap::from([(
+                repetition_key(&words(&number_lines(custom))),
+                "LicenseRef-1".to_string(),
+            )]),
+        };
+        let next_year = custom.replace("2023", "2024") + "fn main() {}\n";
+        assert!(
+            strip(&licenses, &next_year)
+                .expect("repeated header")
+                .starts_with("  [license header: LicenseRef-1]\n3 fn main() {}")
+        );
+    }
+
+    #[test]
+    fn only_files_that_would_be_read_are_scanned() -> anyhow::Result<()> {
+        let dir = crate::test_util::TempDir::new();
+        let header = "// Copyright 2023 Acme Corp. All rights reserved.\n\
+                      // Internal use only.\n";
+        let files = [
+            ("a.rs", format!("{header}fn a() {{}}\n")),
+            ("b.rs", format!("{header}fn b() {{}}\n")),
+            ("c_test.go", format!("{header}package c\n")),
+            (".env", format!("{header}TOKEN=secret\n")),
+            ("d.bin", format!("{header}\0\0\0\0")),
+        ];
+        let options = ReadOptions {
+            no_tests: true,
+            ..ReadOptions::default()
+        };
+        let mut headers = vec![];
+        for (name, contents) in files {
+            let path = dir.path.join(name);
+            std::fs::write(&path, contents)?;
+            let file = DiscoveredFile {
+                path,
+                excluded: false,
+                pruned: None,
+                class: None,
+            };
+            if Licenses::scans(&file, &options) {
+                headers.push(scan_header(&file.path, &options));
+            }
+        }
+
+        assert_eq!(headers.len(), 3);
+        assert!(headers[2].is_none());
+        assert!(
+            Licenses::from_headers(headers.clone(), 2)
+                .repeated
+                .is_empty()
+        );
+        assert_eq!(Licenses::from_headers(headers, 1).repeated.len(), 1);
+
+        Ok(())
+    }
+}
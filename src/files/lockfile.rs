@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use clap::ValueEnum;
+use strum::EnumString;
+
+use super::Line;
+
+/// How lockfiles are rendered.
+#[derive(Default, Debug, Clone, Copy, EnumString, ValueEnum, Eq, Hash, PartialEq)]
+pub enum LockfileMode {
+    /// Full content
+    #[strum(serialize = "full")]
+    Full,
+    /// A `name version` line per resolved package
+    #[default]
+    #[strum(serialize = "summary")]
+    Summary,
+    /// A `name version` line per direct dependency, where the lockfile records them
+    #[strum(serialize = "direct")]
+    Direct,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lockfile {
+    Cargo,
+    Npm,
+    Pnpm,
+    Poetry,
+    GoSum,
+}
+
+impl Lockfile {
+    fn from_path(path: &Path) -> Option<Self> {
+        let lockfile = match path.file_name()?.to_str()? {
+            "Cargo.lock" => Self::Cargo,
+            "package-lock.json" => Self::Npm,
+            "pnpm-lock.yaml" => Self::Pnpm,
+            "poetry.lock" => Self::Poetry,
+            "go.sum" => Self::GoSum,
+            _ => return None,
+        };
+        Some(lockfile)
+    }
+}
+
+/// A resolved package, along with the line it was declared on.
+#[derive(Debug)]
+struct Package<'a> {
+    line: Option<usize>,
+    name: Cow<'a, str>,
+    version: Cow<'a, str>,
+}
+
+/// Renders a lockfile as a `name version` line per package, keeping the line each package was
+/// declared on. Returns `None` for files that aren't recognized lockfiles, or that can't be
+/// parsed.
+///
+/// Only Cargo, npm and pnpm lockfiles record which dependencies are direct, so with
+/// [`LockfileMode::Direct`] other lockfiles still list every package.
+pub(super) fn summarize<'a>(
+    path: &Path,
+    text: &'a str,
+    mode: LockfileMode,
+) -> Option<Vec<Line<'a>>> {
+    if mode == LockfileMode::Full {
+        return None;
+    }
+    let direct_only = mode == LockfileMode::Direct;
+    let mut packages = match Lockfile::from_path(path)? {
+        Lockfile::Cargo => toml_packages(text, direct_only),
+        Lockfile::Poetry => toml_packages(text, false),
+        Lockfile::Npm => npm_packages(text, direct_only)?,
+        Lockfile::Pnpm => pnpm_packages(text, direct_only)?,
+        Lockfile::GoSum => go_sum_packages(text),
+    };
+    packages.sort_by_key(|package| package.line);
+    let lines = packages
+        .into_iter()
+        .map(|package| Line {
+            number: package.line,
+            text: Cow::Owned(format!("{} {}", package.name, package.version)),
+        })
+        .collect();
+    Some(lines)
+}
+
+fn quoted_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let value = line
+        .strip_prefix(key)?
+        .trim_start()
+        .strip_prefix('=')?
+        .trim();
+    value.strip_prefix('"')?.strip_suffix('"')
+}
+
+/// Reads the `[[package]]` tables of `Cargo.lock` and `poetry.lock`.
+///
+/// Packages without a `source` are the workspace's own, and their dependencies are the direct
+/// ones.
+fn toml_packages(text: &str, direct_only: bool) -> Vec<Package<'_>> {
+    struct Table<'a> {
+        package: Option<Package<'a>>,
+        local: bool,
+        dependencies: Vec<&'a str>,
+    }
+
+    let mut tables: Vec<Table> = vec![];
+    let mut in_package = false;
+    let mut in_dependencies = false;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('[') && !in_dependencies {
+            in_package = line == "[[package]]";
+            if in_package {
+                tables.push(Table {
+                    package: None,
+                    local: true,
+                    dependencies: vec![],
+                });
+            }
+            continue;
+        }
+        let Some(table) = tables.last_mut().filter(|_| in_package) else {
+            continue;
+        };
+        if in_dependencies {
+            if line.starts_with(']') {
+                in_dependencies = false;
+            } else if let Some(dependency) = line.trim_end_matches(',').strip_prefix('"') {
+                // "name", or "name version" when several versions of it are locked
+                let dependency = dependency.trim_end_matches('"');
+                let name = dependency.split(' ').next().unwrap_or(dependency);
+                table.dependencies.push(name);
+            }
+        } else if let Some(name) = quoted_value(line, "name") {
+            table.package = Some(Package {
+                line: Some(index + 1),
+                name: Cow::Borrowed(name),
+                version: Cow::Borrowed(""),
+            });
+        } else if let Some(version) = quoted_value(line, "version") {
+            if let Some(package) = &mut table.package {
+                package.version = Cow::Borrowed(version);
+            }
+        } else if line.starts_with("source") {
+            table.local = false;
+        } else if line.starts_with("dependencies = [") {
+            in_dependencies = !line.ends_with(']');
+        }
+    }
+
+    let direct = tables
+        .iter()
+        .filter(|table| table.local)
+        .flat_map(|table| table.dependencies.iter().copied())
+        .collect::<HashSet<_>>();
+    tables
+        .into_iter()
+        .filter(|table| !direct_only || !table.local)
+        .filter_map(|table| table.package)
+        .filter(|package| !direct_only || direct.contains(package.name.as_ref()))
+        .collect()
+}
+
+/// Maps the keys of JSON objects to the line they first appear on.
+fn json_key_lines(text: &str) -> HashMap<&str, usize> {
+    let mut lines = HashMap::new();
+    for (index, line) in text.lines().enumerate() {
+        if let Some(key) = line
+            .trim()
+            .strip_prefix('"')
+            .and_then(|line| line.strip_suffix("\": {"))
+        {
+            lines.entry(key).or_insert(index + 1);
+        }
+    }
+    lines
+}
+
+/// Reads `package-lock.json`, from its `packages` for lockfile versions 2 and up, or its
+/// `dependencies` otherwise.
+fn npm_packages(text: &str, direct_only: bool) -> Option<Vec<Package<'static>>> {
+    let lock: serde_json::Value = serde_json::from_str(text).ok()?;
+    let key_lines = json_key_lines(text);
+    let version = |package: &serde_json::Value| {
+        package
+            .get("version")
+            .and_then(|version| version.as_str())
+            .map(|version| Cow::Owned(version.to_string()))
+    };
+
+    let Some(packages) = lock
+        .get("packages")
+        .and_then(|packages| packages.as_object())
+    else {
+        let dependencies = lock.get("dependencies")?.as_object()?;
+        let packages = dependencies
+            .iter()
+            .filter_map(|(name, package)| {
+                Some(Package {
+                    line: key_lines.get(name.as_str()).copied(),
+                    name: Cow::Owned(name.clone()),
+                    version: version(package)?,
+                })
+            })
+            .collect();
+        return Some(packages);
+    };
+
+    let root = packages.get("");
+    let direct = [
+        "dependencies",
+        "devDependencies",
+        "optionalDependencies",
+        "peerDependencies",
+    ]
+    .into_iter()
+    .filter_map(|field| root?.get(field)?.as_object())
+    .flat_map(|dependencies| dependencies.keys())
+    .collect::<HashSet<_>>();
+
+    let packages = packages
+        .iter()
+        .filter(|(key, _)| !key.is_empty())
+        .filter_map(|(key, package)| {
+            let name = key.rsplit("node_modules/").next().unwrap_or(key);
+            let top_level = key.strip_prefix("node_modules/") == Some(name);
+            if direct_only && !(top_level && direct.contains(&name.to_string())) {
+                return None;
+            }
+            Some(Package {
+                line: key_lines.get(key.as_str()).copied(),
+                name: Cow::Owned(name.to_string()),
+                version: version(package)?,
+            })
+        })
+        .collect();
+    Some(packages)
+}
+
+/// Reads the `packages` of `pnpm-lock.yaml`, keyed like `/name@1.0.0` or `/name/1.0.0` in older
+/// lockfile versions.
+fn pnpm_packages(text: &str, direct_only: bool) -> Option<Vec<Package<'_>>> {
+    let lock: serde_norway::Value = serde_norway::from_str(text).ok()?;
+    let dependency_names = |mapping: &serde_norway::Value| {
+        ["dependencies", "devDependencies", "optionalDependencies"]
+            .into_iter()
+            .filter_map(|field| mapping.get(field)?.as_mapping())
+            .flat_map(|dependencies| dependencies.keys())
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect::<Vec<_>>()
+    };
+    // older lockfiles without importers list the root's dependencies at the top level
+    let direct = lock
+        .get("importers")
+        .and_then(|importers| importers.as_mapping())
+        .map_or_else(
+            || dependency_names(&lock),
+            |importers| importers.values().flat_map(dependency_names).collect(),
+        )
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let mut packages = vec![];
+    let mut in_packages = false;
+    for (index, line) in text.lines().enumerate() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        // package keys are indented by two spaces
+        let Some(key) = line
+            .strip_prefix("  ")
+            .filter(|key| in_packages && !key.starts_with(' '))
+            .and_then(|key| key.trim_end().strip_suffix(':'))
+        else {
+            continue;
+        };
+        let key = key.trim_matches(['\'', '"']);
+        let key = key.strip_prefix('/').unwrap_or(key);
+        // drop peer dependency suffixes like `(react@18.2.0)`
+        let key = key.split('(').next().unwrap_or(key);
+        let Some((name, version)) = key
+            .rfind('@')
+            .filter(|&at| at > 0)
+            .map(|at| (&key[..at], &key[at + 1..]))
+            .or_else(|| key.rsplit_once('/'))
+        else {
+            continue;
+        };
+        if direct_only && !direct.contains(name) {
+            continue;
+        }
+        packages.push(Package {
+            line: Some(index + 1),
+            name: Cow::Borrowed(name),
+            version: Cow::Borrowed(version),
+        });
+    }
+    Some(packages)
+}
+
+/// Reads `go.sum`, which lists each module version once for its content and once for its
+/// `go.mod`.
+fn go_sum_packages(text: &str) -> Vec<Package<'_>> {
+    let mut seen = HashSet::new();
+    let mut packages = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let version = version.strip_suffix("/go.mod").unwrap_or(version);
+        if seen.insert((name, version)) {
+            packages.push(Package {
+                line: Some(index + 1),
+                name: Cow::Borrowed(name),
+                version: Cow::Borrowed(version),
+            });
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(path: &str, text: &str, mode: LockfileMode) -> Vec<(Option<usize>, String)> {
+        summarize(Path::new(path), text, mode)
+            .expect("recognized lockfile")
+            .into_iter()
+            .map(|line| (line.number, line.text.into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn cargo_lock_lists_packages_and_direct_dependencies() {
+        let text = r#"version = 4
+
+[[package]]
+name = "anyhow"
+version = "1.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "anyhow",
+ "serde 1.0.228",
+]
+
+[[package]]
+name = "itoa"
+version = "1.0.15"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.228"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "itoa",
+]
+"#;
+        assert_eq!(
+            render("Cargo.lock", text, LockfileMode::Summary),
+            vec![
+                (Some(4), "anyhow 1.0.100".to_string()),
+                (Some(9), "app 0.1.0".to_string()),
+                (Some(17), "itoa 1.0.15".to_string()),
+                (Some(22), "serde 1.0.228".to_string()),
+            ]
+        );
+        assert_eq!(
+            render("Cargo.lock", text, LockfileMode::Direct),
+            vec![
+                (Some(4), "anyhow 1.0.100".to_string()),
+                (Some(22), "serde 1.0.228".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_lock_lists_top_level_direct_dependencies() {
+        let text = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": {
+      "dependencies": { "a": "^1.0.0" }
+    },
+    "node_modules/a": {
+      "version": "1.2.0"
+    },
+    "node_modules/a/node_modules/b": {
+      "version": "2.0.0"
+    }
+  }
+}
+"#;
+        assert_eq!(
+            render("package-lock.json", text, LockfileMode::Summary),
+            vec![
+                (Some(7), "a 1.2.0".to_string()),
+                (Some(10), "b 2.0.0".to_string()),
+            ]
+        );
+        assert_eq!(
+            render("package-lock.json", text, LockfileMode::Direct),
+            vec![(Some(7), "a 1.2.0".to_string())]
+        );
+    }
+
+    #[test]
+    fn pnpm_lock_and_go_sum_are_summarized() {
+        let pnpm = r#"lockfileVersion: '9.0'
+
+importers:
+  .:
+    dependencies:
+      react:
+        specifier: ^18.2.0
+        version: 18.2.0
+
+packages:
+  '@scope/pkg@1.0.0':
+    resolution: {integrity: sha512-x}
+  react@18.2.0:
+    resolution: {integrity: sha512-y}
+"#;
+        assert_eq!(
+            render("pnpm-lock.yaml", pnpm, LockfileMode::Summary),
+            vec![
+                (Some(11), "@scope/pkg 1.0.0".to_string()),
+                (Some(13), "react 18.2.0".to_string()),
+            ]
+        );
+        assert_eq!(
+            render("pnpm-lock.yaml", pnpm, LockfileMode::Direct),
+            vec![(Some(13), "react 18.2.0".to_string())]
+        );
+
+        let go_sum = "golang.org/x/text v0.3.0 h1:abc=\ngolang.org/x/text v0.3.0/go.mod h1:def=\n";
+        assert_eq!(
+            render("go.sum", go_sum, LockfileMode::Summary),
+            vec![(Some(1), "golang.org/x/text v0.3.0".to_string())]
+        );
+    }
+}
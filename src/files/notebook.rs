@@ -0,0 +1,172 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Notebook {
+    cells: Vec<Cell>,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: MultilineText,
+    #[serde(default)]
+    outputs: Vec<Output>,
+}
+
+/// Text stored either as one string or as a list of lines, as notebooks allow both.
+#[derive(Default, Deserialize)]
+#[serde(untagged)]
+enum MultilineText {
+    #[default]
+    Empty,
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl MultilineText {
+    fn text(&self) -> String {
+        match self {
+            Self::Empty => String::new(),
+            Self::Text(text) => text.clone(),
+            Self::Lines(lines) => lines.concat(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "output_type", rename_all = "snake_case")]
+enum Output {
+    Stream {
+        #[serde(default)]
+        text: MultilineText,
+    },
+    ExecuteResult {
+        #[serde(default)]
+        data: OutputData,
+    },
+    DisplayData {
+        #[serde(default)]
+        data: OutputData,
+    },
+    Error {
+        ename: String,
+        evalue: String,
+    },
+}
+
+/// Output in several MIME types, of which only plain text is kept.
+#[derive(Default, Deserialize)]
+struct OutputData {
+    #[serde(rename = "text/plain")]
+    plain: Option<MultilineText>,
+}
+
+impl Output {
+    fn text(&self) -> Option<String> {
+        match self {
+            Self::Stream { text } => Some(text.text()),
+            Self::ExecuteResult { data } | Self::DisplayData { data } => {
+                data.plain.as_ref().map(MultilineText::text)
+            }
+            Self::Error { ename, evalue } => Some(format!("{ename}: {evalue}")),
+        }
+    }
+}
+
+pub(super) fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Renders a Jupyter notebook cell by cell, with text outputs truncated to `output_lines` lines
+/// each if given. Image outputs and execution metadata are dropped.
+///
+/// Returns `None` if the notebook can't be parsed.
+pub(super) fn render(text: &str, output_lines: Option<usize>) -> Option<String> {
+    let notebook: Notebook = serde_json::from_str(text).ok()?;
+    let mut rendered = String::new();
+    for (index, cell) in notebook.cells.iter().enumerate() {
+        let number = index + 1;
+        if !rendered.is_empty() {
+            rendered.push('\n');
+        }
+        let _ = writeln!(rendered, "[cell {number}: {}]", cell.cell_type);
+        push_text(&mut rendered, &cell.source.text(), None);
+
+        let outputs = cell
+            .outputs
+            .iter()
+            .filter_map(Output::text)
+            .filter(|text| !text.trim().is_empty())
+            .collect::<Vec<_>>();
+        if outputs.is_empty() || output_lines == Some(0) {
+            continue;
+        }
+        let _ = writeln!(rendered, "[cell {number} output]");
+        push_text(&mut rendered, &outputs.concat(), output_lines);
+    }
+    Some(rendered)
+}
+
+fn push_text(rendered: &mut String, text: &str, max_lines: Option<usize>) {
+    let lines = text.lines().collect::<Vec<_>>();
+    let kept = max_lines.unwrap_or(lines.len()).min(lines.len());
+    for line in &lines[..kept] {
+        rendered.push_str(line);
+        rendered.push('\n');
+    }
+    match lines.len() - kept {
+        0 => {}
+        1 => rendered.push_str("... (1 more line)\n"),
+        more => {
+            let _ = writeln!(rendered, "... ({more} more lines)");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notebooks_are_rendered_cell_by_cell() {
+        let notebook = r##"{
+  "cells": [
+    {"cell_type": "markdown", "metadata": {}, "source": ["# Title\n", "Intro"]},
+    {
+      "cell_type": "code",
+      "execution_count": 3,
+      "metadata": {},
+      "source": "for i in range(3):\n    print(i)",
+      "outputs": [
+        {"output_type": "stream", "name": "stdout", "text": ["0\n", "1\n", "2\n"]},
+        {"output_type": "display_data", "metadata": {}, "data": {"image/png": "iVBORw0KGgo="}}
+      ]
+    },
+    {
+      "cell_type": "code",
+      "execution_count": 4,
+      "metadata": {},
+      "source": "1 / 0",
+      "outputs": [{"output_type": "error", "ename": "ZeroDivisionError", "evalue": "division by zero", "traceback": []}]
+    }
+  ],
+  "metadata": {},
+  "nbformat": 4,
+  "nbformat_minor": 5
+}"##;
+
+        assert_eq!(
+            render(notebook, Some(2)).expect("valid notebook"),
+            "[cell 1: markdown]\n# Title\nIntro\n\n\
+             [cell 2: code]\nfor i in range(3):\n    print(i)\n\
+             [cell 2 output]\n0\n1\n... (1 more line)\n\n\
+             [cell 3: code]\n1 / 0\n\
+             [cell 3 output]\nZeroDivisionError: division by zero\n"
+        );
+    }
+}
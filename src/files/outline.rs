@@ -0,0 +1,469 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use tree_sitter::{Node, Parser};
+
+use super::Line;
+
+const ELIDED_BODY: &str = "{ … }";
+const ELIDED_PYTHON_BODY: &str = "...";
+
+/// Languages that can be outlined, each backed by a tree-sitter grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grammar {
+    Rust,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Python,
+    Go,
+}
+
+/// How a declaration is rendered in an outline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Declaration {
+    /// Kept whole, e.g. structs, type aliases and signatures without bodies.
+    Whole,
+    /// Kept with its body elided.
+    Function,
+    /// Kept with its body outlined in turn, e.g. impls and classes.
+    Container,
+    /// Wraps a declaration, e.g. exports and Python decorators.
+    Wrapper,
+}
+
+impl Grammar {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let grammar = match extension.as_str() {
+            "rs" => Self::Rust,
+            "js" | "jsx" | "mjs" | "cjs" => Self::JavaScript,
+            "ts" | "mts" | "cts" => Self::TypeScript,
+            "tsx" => Self::Tsx,
+            "py" | "pyi" => Self::Python,
+            "go" => Self::Go,
+            _ => return None,
+        };
+        Some(grammar)
+    }
+
+    fn language(self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+
+    fn classify(self, node: Node) -> Option<Declaration> {
+        let declaration = match (self, node.kind()) {
+            (
+                Self::Rust,
+                "struct_item"
+                | "enum_item"
+                | "union_item"
+                | "type_item"
+                | "const_item"
+                | "static_item"
+                | "function_signature_item",
+            ) => Declaration::Whole,
+            (Self::Rust, "function_item" | "macro_definition") => Declaration::Function,
+            (Self::Rust, "trait_item" | "impl_item" | "mod_item") => Declaration::Container,
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "interface_declaration"
+                | "type_alias_declaration"
+                | "enum_declaration"
+                | "function_signature"
+                | "method_signature"
+                | "abstract_method_signature",
+            ) => Declaration::Whole,
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "function_declaration" | "generator_function_declaration" | "method_definition",
+            ) => Declaration::Function,
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "class_declaration" | "abstract_class_declaration",
+            ) => Declaration::Container,
+            (
+                Self::JavaScript | Self::TypeScript | Self::Tsx,
+                "lexical_declaration" | "variable_declaration",
+            ) if function_value(node).is_some() => Declaration::Function,
+            (Self::JavaScript | Self::TypeScript | Self::Tsx, "export_statement") => {
+                Declaration::Wrapper
+            }
+            (Self::Python, "expression_statement") if is_docstring(node) => Declaration::Whole,
+            (Self::Python, "function_definition") => Declaration::Function,
+            (Self::Python, "class_definition") => Declaration::Container,
+            (Self::Python, "decorated_definition") => Declaration::Wrapper,
+            (Self::Go, "type_declaration") => Declaration::Whole,
+            (Self::Go, "function_declaration" | "method_declaration") => Declaration::Function,
+            _ => return None,
+        };
+        Some(declaration)
+    }
+
+    /// Whether the node is kept along with the declaration following it, like a doc comment.
+    fn is_leading(self, node: Node, source: &str) -> bool {
+        let text = || node.utf8_text(source.as_bytes()).unwrap_or_default();
+        match (self, node.kind()) {
+            (Self::Rust, "attribute_item") => true,
+            (Self::Rust, "line_comment") => {
+                text().starts_with("///") && !text().starts_with("////")
+            }
+            (Self::Rust, "block_comment") => text().starts_with("/**") && text() != "/**/",
+            (Self::JavaScript | Self::TypeScript | Self::Tsx, "comment") => {
+                text().starts_with("/**")
+            }
+            // Go doc comments are plain comments directly above a declaration
+            (Self::Go, "comment") => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the node is a comment, which doesn't separate a declaration from its doc comments.
+    fn is_comment(self, node: Node) -> bool {
+        match self {
+            Self::Rust => matches!(node.kind(), "line_comment" | "block_comment"),
+            _ => node.kind() == "comment",
+        }
+    }
+
+    /// Whether bodies are delimited by indentation rather than braces.
+    fn is_indented(self) -> bool {
+        self == Self::Python
+    }
+}
+
+/// The function or arrow function a variable declaration like `const f = () => {}` binds.
+fn function_value(node: Node) -> Option<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|child| child.kind() == "variable_declarator")
+        .filter_map(|declarator| declarator.child_by_field_name("value"))
+        .find(|value| {
+            matches!(
+                value.kind(),
+                "arrow_function"
+                    | "function_expression"
+                    | "function"
+                    | "generator_function"
+                    | "generator_function_expression"
+            )
+        })
+}
+
+/// The braced body of a declaration that has one, including the rules of a `macro_rules!` and
+/// the block of a function bound to a variable.
+fn body(node: Node) -> Option<Node> {
+    match node.kind() {
+        "macro_definition" => {
+            let mut cursor = node.walk();
+            node.children(&mut cursor)
+                .find(|child| matches!(child.kind(), "{" | "(" | "["))
+        }
+        "lexical_declaration" | "variable_declaration" => function_value(node)?
+            .child_by_field_name("body")
+            .filter(|body| body.kind() == "statement_block"),
+        _ => node.child_by_field_name("body"),
+    }
+}
+
+fn is_docstring(node: Node) -> bool {
+    node.kind() == "expression_statement"
+        && node.prev_named_sibling().is_none()
+        && node
+            .named_child(0)
+            .is_some_and(|child| child.kind() == "string")
+}
+
+/// Renders only the declarations in a source file, with function bodies elided, keeping the
+/// original line numbers. Returns `None` for languages that can't be outlined.
+pub(super) fn outline<'a>(path: &Path, text: &'a str) -> Option<Vec<Line<'a>>> {
+    let grammar = Grammar::from_path(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language()).ok()?;
+    let tree = parser.parse(text, None)?;
+
+    let mut outliner = Outliner {
+        grammar,
+        source: text,
+        lines: vec![],
+    };
+    outliner.outline_scope(tree.root_node());
+    Some(outliner.lines)
+}
+
+struct Outliner<'a> {
+    grammar: Grammar,
+    source: &'a str,
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Outliner<'a> {
+    fn outline_scope(&mut self, scope: Node) {
+        let mut cursor = scope.walk();
+        let mut lead = None;
+        for child in scope.named_children(&mut cursor) {
+            if self.grammar.is_leading(child, self.source) {
+                lead.get_or_insert(child);
+                continue;
+            }
+            if self.grammar.is_comment(child) {
+                continue;
+            }
+            if let Some(declaration) = self.grammar.classify(child) {
+                self.outline_declaration(lead.unwrap_or(child), child, declaration);
+            }
+            lead = None;
+        }
+    }
+
+    /// Outlines `node`, starting from `lead` to keep its doc comments, attributes and wrappers.
+    fn outline_declaration(&mut self, lead: Node, node: Node, declaration: Declaration) {
+        match (declaration, body(node)) {
+            (Declaration::Whole, _) | (Declaration::Function | Declaration::Container, None) => {
+                self.push_span(lead, node.end_byte());
+            }
+            (Declaration::Function, Some(body)) => {
+                self.push_span(lead, body.start_byte());
+                if self.grammar.is_indented() {
+                    self.end_last_line("");
+                    if let Some(docstring) =
+                        body.named_child(0).filter(|child| is_docstring(*child))
+                    {
+                        self.push_span(docstring, docstring.end_byte());
+                    }
+                    let indent = " ".repeat(body.start_position().column);
+                    self.lines.push(Line {
+                        number: None,
+                        text: Cow::Owned(format!("{indent}{ELIDED_PYTHON_BODY}")),
+                    });
+                } else {
+                    self.end_last_line(&format!(" {ELIDED_BODY}"));
+                }
+            }
+            (Declaration::Container, Some(body)) => {
+                self.push_span(lead, body.start_byte());
+                if self.grammar.is_indented() {
+                    self.end_last_line("");
+                    self.outline_scope(body);
+                } else {
+                    self.end_last_line(" {");
+                    self.outline_scope(body);
+                    self.close(body);
+                }
+            }
+            (Declaration::Wrapper, _) => {
+                let inner = node
+                    .child_by_field_name("declaration")
+                    .or_else(|| node.child_by_field_name("definition"));
+                if let Some(inner) = inner
+                    && let Some(declaration) = self.grammar.classify(inner)
+                {
+                    self.outline_declaration(lead, inner, declaration);
+                }
+            }
+        }
+    }
+
+    /// Pushes the source lines from the start of the line `start` is on up to `end`, leaving
+    /// out whitespace before `end`, such as the indentation of a Python body.
+    fn push_span(&mut self, start: Node, end: usize) {
+        let from = self.source[..start.start_byte()]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        let first_number = start.start_position().row + 1;
+        let span = self.source[from..end].trim_end();
+        for (offset, text) in span.split('\n').enumerate() {
+            self.lines.push(Line {
+                number: Some(first_number + offset),
+                text: Cow::Borrowed(text.strip_suffix('\r').unwrap_or(text)),
+            });
+        }
+    }
+
+    fn end_last_line(&mut self, suffix: &str) {
+        if let Some(line) = self.lines.last_mut() {
+            line.text = Cow::Owned(format!("{}{suffix}", line.text.trim_end()));
+        }
+    }
+
+    /// Pushes the line closing a braced body, unless it closes on the last pushed line.
+    fn close(&mut self, body: Node) {
+        let number = body.end_position().row + 1;
+        if self.lines.last().and_then(|line| line.number) == Some(number) {
+            self.end_last_line(" }");
+            return;
+        }
+        let line = self.source[..body.end_byte()]
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default();
+        self.lines.push(Line {
+            number: Some(number),
+            text: Cow::Borrowed(line),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(path: &str, text: &str) -> Vec<(Option<usize>, String)> {
+        outline(Path::new(path), text)
+            .expect("supported language")
+            .into_iter()
+            .map(|line| (line.number, line.text.into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn rust_bodies_are_elided() {
+        let text = r#"use std::fmt;
+
+/// A point.
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+}
+
+// not a doc comment
+impl Point {
+    /// Creates a point.
+    pub fn new(x: i32) -> Self {
+        Self { x }
+    }
+}
+
+fn main() {
+    println!("hi");
+}
+"#;
+        assert_eq!(
+            render("src/main.rs", text),
+            vec![
+                (Some(3), "/// A point.".to_string()),
+                (Some(4), "#[derive(Debug)]".to_string()),
+                (Some(5), "pub struct Point {".to_string()),
+                (Some(6), "    x: i32,".to_string()),
+                (Some(7), "}".to_string()),
+                (Some(10), "impl Point {".to_string()),
+                (Some(11), "    /// Creates a point.".to_string()),
+                (Some(12), "    pub fn new(x: i32) -> Self { … }".to_string()),
+                (Some(15), "}".to_string()),
+                (Some(17), "fn main() { … }".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_constants_macros_and_separated_docs_are_kept() {
+        let text = r#"/// The limit.
+// TODO: make configurable
+pub const LIMIT: usize = 10;
+static NAME: &str = "name";
+pub type Id = u64;
+
+/// Squares.
+macro_rules! square {
+    ($x:expr) => {
+        $x * $x
+    };
+}
+"#;
+        assert_eq!(
+            render("src/lib.rs", text),
+            vec![
+                (Some(1), "/// The limit.".to_string()),
+                (Some(2), "// TODO: make configurable".to_string()),
+                (Some(3), "pub const LIMIT: usize = 10;".to_string()),
+                (Some(4), r#"static NAME: &str = "name";"#.to_string()),
+                (Some(5), "pub type Id = u64;".to_string()),
+                (Some(7), "/// Squares.".to_string()),
+                (Some(8), "macro_rules! square { … }".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_bodies_keep_docstrings() {
+        let text = r#"import os
+
+@dataclass
+class Point:
+    """A point."""
+
+    def norm(self) -> float:
+        """Returns the norm."""
+        return abs(self.x)
+"#;
+        assert_eq!(
+            render("point.py", text),
+            vec![
+                (Some(3), "@dataclass".to_string()),
+                (Some(4), "class Point:".to_string()),
+                (Some(5), r#"    """A point.""""#.to_string()),
+                (Some(7), "    def norm(self) -> float:".to_string()),
+                (Some(8), r#"        """Returns the norm.""""#.to_string()),
+                (None, "        ...".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn typescript_exports_and_classes_are_outlined() {
+        let text = r#"/** Greets. */
+export function greet(name: string): string {
+  return `hi ${name}`;
+}
+
+export interface Named { name: string }
+
+/** Handles events. */
+export const handler = async (event: Event): Promise<void> => {
+  await process(event);
+};
+const double = (x: number) => x * 2;
+const limit = 10;
+
+class Greeter {
+  greet() { return "hi"; }
+}
+"#;
+        assert_eq!(
+            render("greet.ts", text),
+            vec![
+                (Some(1), "/** Greets. */".to_string()),
+                (
+                    Some(2),
+                    "export function greet(name: string): string { … }".to_string()
+                ),
+                (
+                    Some(6),
+                    "export interface Named { name: string }".to_string()
+                ),
+                (Some(8), "/** Handles events. */".to_string()),
+                (
+                    Some(9),
+                    "export const handler = async (event: Event): Promise<void> => { … }"
+                        .to_string()
+                ),
+                (Some(12), "const double = (x: number) => x * 2;".to_string()),
+                (Some(15), "class Greeter {".to_string()),
+                (Some(16), "  greet() { … }".to_string()),
+                (Some(17), "}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_languages_are_not_outlined() {
+        assert!(outline(Path::new("notes.txt"), "text").is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use tree_sitter::{Node, Parser};
+
+use super::Line;
+
+/// A `#[cfg(test)]` item in a Rust file, with its attributes and doc comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TestItem {
+    /// Line numbers the item spans.
+    lines: RangeInclusive<usize>,
+    indent: usize,
+}
+
+/// Finds the `#[cfg(test)]` items of a Rust file, at the top level and in modules, impls and
+/// traits. Returns an empty list for other files.
+pub(super) fn test_items(path: &Path, text: &str) -> Vec<TestItem> {
+    if path.extension().and_then(|extension| extension.to_str()) != Some("rs") {
+        return vec![];
+    }
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&tree_sitter_rust::LANGUAGE.into())
+        .is_err()
+    {
+        return vec![];
+    }
+    let Some(tree) = parser.parse(text, None) else {
+        return vec![];
+    };
+    let mut items = vec![];
+    collect_test_items(tree.root_node(), text, &mut items);
+    items
+}
+
+fn collect_test_items(scope: Node, source: &str, items: &mut Vec<TestItem>) {
+    let mut cursor = scope.walk();
+    // first attribute or doc comment before the next item, and whether any was `#[cfg(test)]`
+    let mut lead = None;
+    let mut cfg_test = false;
+    for child in scope.named_children(&mut cursor) {
+        let text = child.utf8_text(source.as_bytes()).unwrap_or_default();
+        match child.kind() {
+            "attribute_item" => {
+                lead.get_or_insert(child);
+                cfg_test |= is_cfg_test(text);
+                continue;
+            }
+            "line_comment" | "block_comment" => {
+                if text.starts_with("///") || text.starts_with("/**") {
+                    lead.get_or_insert(child);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if cfg_test {
+            let start = lead.unwrap_or(child).start_position();
+            items.push(TestItem {
+                lines: start.row + 1..=child.end_position().row + 1,
+                indent: start.column,
+            });
+        } else if matches!(child.kind(), "mod_item" | "impl_item" | "trait_item")
+            && let Some(body) = child.child_by_field_name("body")
+        {
+            collect_test_items(body, source, items);
+        }
+        lead = None;
+        cfg_test = false;
+    }
+}
+
+/// Whether an attribute is a `cfg` whose predicate only holds in test builds, like
+/// `#[cfg(test)]` or `#[cfg(all(test, feature = "x"))]`.
+fn is_cfg_test(attribute: &str) -> bool {
+    let attribute = attribute
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    attribute
+        .strip_prefix("#[cfg(")
+        .and_then(|rest| rest.strip_suffix(")]"))
+        .is_some_and(requires_test)
+}
+
+/// Whether a cfg predicate can only hold when `test` is set. Predicates under `not` never count.
+fn requires_test(predicate: &str) -> bool {
+    if predicate == "test" {
+        return true;
+    }
+    if let Some(terms) = predicate
+        .strip_prefix("all(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return predicate_terms(terms).any(requires_test);
+    }
+    if let Some(terms) = predicate
+        .strip_prefix("any(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let mut terms = predicate_terms(terms).peekable();
+        return terms.peek().is_some() && terms.all(requires_test);
+    }
+    false
+}
+
+/// Splits the comma separated terms of a cfg predicate list, leaving nested lists and quoted
+/// values whole.
+fn predicate_terms(list: &str) -> impl Iterator<Item = &str> {
+    let mut terms = vec![];
+    let (mut depth, mut quoted, mut start) = (0_usize, false, 0);
+    for (index, c) in list.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth = depth.saturating_sub(1),
+            ',' if !quoted && depth == 0 => {
+                terms.push(&list[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push(&list[start..]);
+    terms.into_iter().filter(|term| !term.is_empty())
+}
+
+/// Replaces the lines of test items with a marker naming the lines left out.
+pub(super) fn elide<'a>(lines: Vec<Line<'a>>, items: &[TestItem]) -> Vec<Line<'a>> {
+    let mut elided = Vec::with_capacity(lines.len());
+    let mut current = None;
+    for line in lines {
+        // markers standing in for other elided content are kept
+        let Some(number) = line.number else {
+            elided.push(line);
+            continue;
+        };
+        match items.iter().position(|item| item.lines.contains(&number)) {
+            Some(index) if current == Some(index) => {}
+            Some(index) => {
+                let item = &items[index];
+                elided.push(Line {
+                    number: None,
+                    text: Cow::Owned(format!(
+                        "{}[test code elided: lines {}-{}]",
+                        " ".repeat(item.indent),
+                        item.lines.start(),
+                        item.lines.end()
+                    )),
+                });
+                current = Some(index);
+            }
+            None => {
+                elided.push(line);
+                current = None;
+            }
+        }
+    }
+    elided
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{LineNumberStyle, annotate_line_numbers, number_lines};
+
+    #[test]
+    fn cfg_test_items_are_elided() {
+        let text = r#"pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+impl Point {
+    /// Only for tests.
+    #[cfg(test)]
+    fn origin() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg( test )]
+// a plain comment
+mod tests {
+    #[test]
+    fn adds() {}
+}
+"#;
+        let items = test_items(Path::new("src/lib.rs"), text);
+        assert_eq!(
+            annotate_line_numbers(&elide(number_lines(text), &items), LineNumberStyle::Plain),
+            " 1 pub fn add(a: i32, b: i32) -> i32 {\n \
+              2     a + b\n \
+              3 }\n \
+              4 \n \
+              5 impl Point {\n       \
+                [test code elided: lines 6-10]\n\
+             11 }\n\
+             12 \n   \
+                [test code elided: lines 13-18]\n"
+        );
+        assert!(test_items(Path::new("lib.py"), text).is_empty());
+    }
+
+    #[test]
+    fn cfg_predicates_requiring_test_are_elided() {
+        assert!(is_cfg_test(r#"#[cfg(all(test, feature = "x"))]"#));
+        assert!(is_cfg_test(
+            r#"#[cfg(all(unix, any(test, all(test, feature = "a,b"))))]"#
+        ));
+        assert!(!is_cfg_test("#[cfg(not(test))]"));
+        assert!(!is_cfg_test("#[cfg(any(test, feature = \"x\"))]"));
+        assert!(!is_cfg_test("#[cfg_attr(test, derive(Debug))]"));
+
+        let text = "#[cfg(all(test, feature = \"x\"))]\nmod tests {}\nfn main() {}\n";
+        let items = test_items(Path::new("src/main.rs"), text);
+        assert_eq!(
+            annotate_line_numbers(&elide(number_lines(text), &items), LineNumberStyle::Plain),
+            "  [test code elided: lines 1-2]\n3 fn main() {}\n"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use strum::EnumString;
+
+use super::comments::{Language, Stripper};
+use super::{Line, LineNumberStyle, RawLineNumberStyle, ReadOptions, strip_dot_prefix};
+
+/// A change made to a file's lines before it is token counted and emitted.
+pub trait Transform: std::fmt::Debug + Send + Sync {
+    fn apply<'a>(&self, path: &Path, lines: Vec<Line<'a>>) -> Vec<Line<'a>>;
+}
+
+#[derive(Debug, Clone, Copy, EnumString, ValueEnum, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransformKind {
+    #[strum(serialize = "collapse-blank-lines")]
+    CollapseBlankLines,
+    #[strum(serialize = "trim-trailing-whitespace")]
+    TrimTrailingWhitespace,
+    /// Strips comments other than doc comments from supported languages.
+    #[strum(serialize = "strip-comments")]
+    StripComments,
+    /// Strips comments including doc comments (and Python docstrings).
+    #[strum(serialize = "strip-doc-comments")]
+    StripDocComments,
+}
+
+impl TransformKind {
+    fn transform(self) -> Box<dyn Transform> {
+        match self {
+            Self::CollapseBlankLines => Box::new(CollapseBlankLines),
+            Self::TrimTrailingWhitespace => Box::new(TrimTrailingWhitespace),
+            Self::StripComments => Box::new(StripComments {
+                doc_comments: false,
+            }),
+            Self::StripDocComments => Box::new(StripComments { doc_comments: true }),
+        }
+    }
+}
+
+/// Collapses runs of blank lines into a single blank line.
+#[derive(Debug)]
+struct CollapseBlankLines;
+
+impl Transform for CollapseBlankLines {
+    fn apply<'a>(&self, _path: &Path, lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        let mut collapsed: Vec<Line<'a>> = Vec::with_capacity(lines.len());
+        for line in lines {
+            let blank = line.text.trim().is_empty();
+            let previous_blank = collapsed
+                .last()
+                .is_some_and(|previous| previous.text.trim().is_empty());
+            if !(blank && previous_blank) {
+                collapsed.push(line);
+            }
+        }
+        collapsed
+    }
+}
+
+#[derive(Debug)]
+struct TrimTrailingWhitespace;
+
+impl Transform for TrimTrailingWhitespace {
+    fn apply<'a>(&self, _path: &Path, lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        lines
+            .into_iter()
+            .map(|mut line| {
+                let trimmed = line.text.trim_end();
+                if trimmed.len() != line.text.len() {
+                    line.text = trimmed.to_string().into();
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+/// Strips comments from languages it recognizes, keeping line numbers of the remaining lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripComments {
+    /// Whether doc comments are stripped too.
+    pub doc_comments: bool,
+}
+
+impl Transform for StripComments {
+    fn apply<'a>(&self, path: &Path, lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        match Language::from_path(path) {
+            Some(language) => Stripper::new(language, !self.doc_comments).strip(lines),
+            None => lines,
+        }
+    }
+}
+
+/// Transforms to apply to files matching a glob, as configured in `.prompt.yaml`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawTransformRule")]
+pub struct TransformRule {
+    /// Files the rule applies to, or every file if `None`.
+    pub glob: Option<glob::Pattern>,
+    pub transforms: Vec<TransformKind>,
+    /// Line number style for matching files, if the rule decides it.
+    pub line_numbers: Option<LineNumberStyle>,
+}
+
+impl TransformRule {
+    /// A rule applying `transforms` to every file.
+    pub const fn everywhere(transforms: Vec<TransformKind>) -> Self {
+        Self {
+            glob: None,
+            transforms,
+            line_numbers: None,
+        }
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        self.glob
+            .as_ref()
+            .is_none_or(|glob| glob.matches_path(strip_dot_prefix(path)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTransformRule {
+    glob: Option<String>,
+    #[serde(default)]
+    transforms: Vec<TransformKind>,
+    line_numbers: Option<RawLineNumberStyle>,
+}
+
+impl TryFrom<RawTransformRule> for TransformRule {
+    type Error = glob::PatternError;
+
+    fn try_from(raw: RawTransformRule) -> Result<Self, Self::Error> {
+        Ok(Self {
+            glob: raw.glob.as_deref().map(glob::Pattern::new).transpose()?,
+            transforms: raw.transforms,
+            line_numbers: raw.line_numbers.map(LineNumberStyle::from),
+        })
+    }
+}
+
+/// The ordered transforms applying to a single file.
+///
+/// Every matching rule contributes its transforms in order, while the last matching rule that
+/// decides on line numbers wins unless the style was set for the whole run.
+#[derive(Debug)]
+pub(super) struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+    pub line_numbers: LineNumberStyle,
+}
+
+impl Pipeline {
+    pub fn for_path(path: &Path, options: &ReadOptions) -> Self {
+        let mut pipeline = Self {
+            transforms: vec![],
+            line_numbers: LineNumberStyle::default(),
+        };
+        // stripping comments first lets later transforms tidy up after it
+        if let Some(strip_comments) = options.strip_comments {
+            pipeline.transforms.push(Box::new(strip_comments));
+        }
+        for rule in options.transforms.iter().filter(|rule| rule.matches(path)) {
+            pipeline
+                .transforms
+                .extend(rule.transforms.iter().map(|kind| kind.transform()));
+            if let Some(line_numbers) = rule.line_numbers {
+                pipeline.line_numbers = line_numbers;
+            }
+        }
+        if let Some(line_numbers) = options.line_numbers {
+            pipeline.line_numbers = line_numbers;
+        }
+        pipeline
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    pub fn apply<'a>(&self, path: &Path, lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        self.transforms
+            .iter()
+            .fold(lines, |lines, transform| transform.apply(path, lines))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::number_lines;
+
+    #[test]
+    fn pipeline_combines_matching_rules() {
+        let rules: Vec<TransformRule> = serde_norway::from_str(
+            r#"
+- transforms: [collapse-blank-lines]
+- glob: "*.md"
+  transforms: [trim-trailing-whitespace]
+  line_numbers: false
+- glob: "*.rs"
+  line_numbers: L-prefix
+- glob: "*.py"
+  line_numbers: l-prefix
+"#,
+        )
+        .expect("valid rules");
+
+        let options = ReadOptions {
+            transforms: rules,
+            ..Default::default()
+        };
+
+        let path = Path::new("./docs/README.md");
+        let markdown = Pipeline::for_path(path, &options);
+        assert_eq!(markdown.line_numbers, LineNumberStyle::Off);
+        let lines = markdown.apply(path, number_lines("# Title  \n\n\n\ntext\n"));
+        let texts = lines
+            .iter()
+            .map(|line| line.text.as_ref())
+            .collect::<Vec<_>>();
+        let numbers = lines.iter().map(|line| line.number).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["# Title", "", "text"]);
+        assert_eq!(numbers, vec![Some(1), Some(2), Some(5)]);
+
+        let rust = Pipeline::for_path(Path::new("src/main.rs"), &options);
+        assert_eq!(rust.line_numbers, LineNumberStyle::LPrefix);
+        assert_eq!(rust.transforms.len(), 1);
+        let python = Pipeline::for_path(Path::new("tool.py"), &options);
+        assert_eq!(python.line_numbers, LineNumberStyle::LPrefix);
+    }
+}
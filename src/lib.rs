@@ -1,5 +1,8 @@
+pub(crate) mod config;
 pub(crate) mod discovery;
 pub(crate) mod files;
 pub mod run;
+#[cfg(test)]
+mod test_util;
 pub mod tokenizer;
 pub(crate) mod tree;
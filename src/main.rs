@@ -1,9 +1,17 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 use anyhow::Result;
+use clap::error::ErrorKind;
 use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{Shell, generate};
-use prompt::run::{self, Format, TokenCountOptions};
+use encoding_rs::Encoding;
+use prompt::run::{
+    self, BinaryDetection, DiscoverOptions, Format, GenerateOptions, Grep, ImageOptions,
+    LineNumberStyle, LockfileMode, ReadOptions, StripComments, TokenCountOptions, TransformKind,
+    TransformRule,
+};
+use regex::Regex;
 use tracing_subscriber::EnvFilter;
 
 const BINARY_NAME: &str = "prompt";
@@ -41,9 +49,258 @@ struct Cli {
     )]
     no_gitignore: bool,
     #[command(flatten)]
+    types: TypeOptions,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATTERN",
+        help = "Only include files whose contents match the given regex"
+    )]
+    grep: Option<Regex>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "LINES",
+        requires = "grep",
+        help = "Only include matching lines from --grep, with this many lines of context around them"
+    )]
+    grep_context: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "TRANSFORM",
+        help = "Transforms to apply to every file, overriding those configured in .prompt.yaml"
+    )]
+    transform: Vec<TransformKind>,
+    #[arg(
+        long,
+        global = true,
+        help = "Strip comments from supported languages, keeping doc comments"
+    )]
+    strip_comments: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Strip comments from supported languages, including doc comments"
+    )]
+    strip_doc_comments: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "STYLE",
+        help = "Line number style for every file, overriding styles configured in .prompt.yaml"
+    )]
+    line_numbers: Option<LineNumberStyle>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "GLOB",
+        help = "Render matching files as an outline of their declarations, eliding bodies (Rust, TS/JS, Python and Go)"
+    )]
+    outline: Vec<glob::Pattern>,
+    #[arg(
+        long,
+        global = true,
+        help = "Refuse to produce a prompt if any secrets were detected, instead of redacting them"
+    )]
+    fail_on_secrets: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "GLOB",
+        help = "Read sensitive files matching the glob, like .env or private keys, which are otherwise never read"
+    )]
+    allow_sensitive: Vec<glob::Pattern>,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "MODE",
+        default_value_t = LockfileMode::default(),
+        help = "How to render lockfiles like Cargo.lock and package-lock.json"
+    )]
+    lockfiles: LockfileMode,
+    #[arg(
+        long,
+        global = true,
+        value_name = "LINES",
+        help = "Truncate each Jupyter notebook cell's output to this many lines"
+    )]
+    notebook_output_lines: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "Include the plain text of .docx, .odt and .pdf documents instead of excluding them as binary"
+    )]
+    extract_documents: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "LABEL",
+        value_parser = parse_encoding,
+        help = "Encoding of files without a byte order mark, e.g. latin1 or shift_jis, instead of detecting it"
+    )]
+    encoding: Option<&'static Encoding>,
+    #[arg(
+        long,
+        global = true,
+        help = "Attach PNG, JPEG, GIF and WebP images to json and yaml output instead of excluding them as binary"
+    )]
+    images: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "BYTES",
+        default_value_t = ImageOptions::default().max_bytes,
+        help = "Leave out images larger than this, after any downscaling"
+    )]
+    image_max_bytes: u64,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PIXELS",
+        help = "Downscale images whose longer edge is larger than this"
+    )]
+    image_max_dimension: Option<u32>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "ROWS",
+        help = "Rows of CSV and TSV files, or items of JSON arrays, to sample alongside their inferred schema [default: 5]"
+    )]
+    sample_rows: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "GLOB",
+        help = "Include data files matching the glob in full, rather than as a schema and sample"
+    )]
+    full_data: Vec<glob::Pattern>,
+    #[arg(
+        long,
+        global = true,
+        help = "Describe binary files, like the schema of SQLite databases or the listing of archives, instead of excluding them"
+    )]
+    describe_binaries: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "GLOB",
+        help = "Always read files matching the glob as text, whatever their content"
+    )]
+    text: Vec<glob::Pattern>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "GLOB",
+        help = "Always treat files matching the glob as binary, whatever their content"
+    )]
+    binary: Vec<glob::Pattern>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "BYTES",
+        default_value_t = BinaryDetection::default().sample_bytes,
+        help = "Bytes sampled from the start of each file to detect binary files"
+    )]
+    binary_sample_bytes: usize,
+    #[arg(
+        long,
+        global = true,
+        help = "Read generated files, marked by `linguist-generated` in .gitattributes or a header like `DO NOT EDIT`"
+    )]
+    include_generated: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Read vendored files, under vendor/ or third_party/ or marked by `linguist-vendored` in .gitattributes"
+    )]
+    include_vendored: bool,
+    #[arg(long, global = true, help = "Read minified scripts and stylesheets")]
+    include_minified: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Include every copy of files with identical content, rather than only the first"
+    )]
+    keep_duplicates: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SIMILARITY",
+        value_parser = parse_similarity,
+        conflicts_with = "keep_duplicates",
+        help = "Also leave out files at least this similar to an earlier file, from 0 to 1, e.g. 0.9"
+    )]
+    near_duplicates: Option<f64>,
+    #[arg(
+        long,
+        global = true,
+        help = "Keep license headers instead of replacing them with a line naming the license"
+    )]
+    keep_license_headers: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILES",
+        conflicts_with = "keep_license_headers",
+        help = "Take header comments mentioning a copyright or license as license headers when shared by more than this many files [default: 2]"
+    )]
+    license_repeats: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "keep_license_headers",
+        help = "Include the full text of replaced license headers once at the top of the prompt"
+    )]
+    license_texts: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Leave out tests: `#[cfg(test)]` items in Rust files, files under tests/ and benches/, and files like *_test.go, test_*.py and *.spec.ts"
+    )]
+    no_tests: bool,
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_name = "N",
+        help = "Files to read and tokenize at once [default: number of CPUs]"
+    )]
+    jobs: Option<NonZeroUsize>,
+    #[command(flatten)]
     output: OutputOptions,
 }
 
+#[derive(Debug, Args)]
+struct TypeOptions {
+    #[arg(
+        short = 't',
+        long = "type",
+        global = true,
+        value_name = "TYPE",
+        help = "Only include files matching the given file type (see `prompt types`)"
+    )]
+    types: Vec<String>,
+    #[arg(
+        short = 'T',
+        long = "type-not",
+        global = true,
+        value_name = "TYPE",
+        help = "Exclude files matching the given file type (see `prompt types`)"
+    )]
+    types_not: Vec<String>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "TYPE_SPEC",
+        help = "Add a custom file type definition, e.g. 'web:*.{html,css}'"
+    )]
+    type_add: Vec<String>,
+}
+
 // default - prompt clip, summary stdout
 // prompt stdout, summary NO
 // prompt stdout, summary stdout
@@ -64,6 +321,11 @@ struct OutputOptions {
         help = "Token count nothing, the final output or also each individual file"
     )]
     token_count: TokenCountOptions,
+    #[arg(
+        long,
+        help = "Also tokenize the whole output and report how far the final count derived from per-file counts is from it (implies --token-count each)"
+    )]
+    verify_count: bool,
 }
 
 #[derive(Debug, Default, Subcommand, Clone)]
@@ -87,6 +349,24 @@ enum Command {
     )]
         top: Option<u32>,
     },
+    /// List the file types usable with --type and --type-not
+    Types,
+    /// Explain why files are included in or excluded from the prompt
+    Explain {
+        #[arg(required = true, value_name = "FILE")]
+        targets: Vec<PathBuf>,
+    },
+}
+
+fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.as_bytes()).ok_or_else(|| format!("unknown encoding `{label}`"))
+}
+
+fn parse_similarity(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(similarity) if (0.0..=1.0).contains(&similarity) => Ok(similarity),
+        _ => Err(format!("`{value}` isn't a similarity from 0 to 1")),
+    }
 }
 
 #[tokio::main]
@@ -104,17 +384,84 @@ async fn main() -> Result<()> {
     let first_path = first_path.to_owned();
     let rest_paths = rest_paths.to_vec();
 
+    let discover_options = DiscoverOptions {
+        exclude: cli.exclude,
+        no_gitignore: cli.no_gitignore,
+        types: cli.types.types,
+        types_not: cli.types.types_not,
+        type_add: cli.types.type_add,
+        allow_sensitive: cli.allow_sensitive.clone(),
+    };
+    let read_options = ReadOptions {
+        grep: cli.grep.map(|pattern| Grep {
+            pattern,
+            context: cli.grep_context,
+        }),
+        transforms: if cli.transform.is_empty() {
+            vec![]
+        } else {
+            vec![TransformRule::everywhere(cli.transform)]
+        },
+        strip_comments: (cli.strip_comments || cli.strip_doc_comments).then_some(StripComments {
+            doc_comments: cli.strip_doc_comments,
+        }),
+        line_numbers: cli.line_numbers,
+        outline: cli.outline,
+        fail_on_secrets: cli.fail_on_secrets,
+        allow_sensitive: cli.allow_sensitive,
+        lockfiles: cli.lockfiles,
+        notebook_output_lines: cli.notebook_output_lines,
+        extract_documents: cli.extract_documents,
+        encoding: cli.encoding,
+        images: cli.images.then_some(ImageOptions {
+            max_bytes: cli.image_max_bytes,
+            max_dimension: cli.image_max_dimension,
+        }),
+        sample_rows: cli.sample_rows,
+        full_data: cli.full_data,
+        describe_binaries: cli.describe_binaries,
+        binary_detection: BinaryDetection {
+            text: cli.text,
+            binary: cli.binary,
+            sample_bytes: cli.binary_sample_bytes,
+        },
+        include_generated: cli.include_generated,
+        include_vendored: cli.include_vendored,
+        include_minified: cli.include_minified,
+        keep_duplicates: cli.keep_duplicates,
+        near_duplicates: cli.near_duplicates,
+        keep_license_headers: cli.keep_license_headers,
+        license_repeats: cli.license_repeats,
+        license_texts: cli.license_texts,
+        no_tests: cli.no_tests,
+        jobs: cli.jobs,
+    };
+
     let command = cli.command.unwrap_or_default();
     match command {
         Command::Generate => {
+            let token_count = match cli.output.token_count {
+                TokenCountOptions::None if cli.output.verify_count => Cli::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "--verify-count can't be used with --token-count none",
+                    )
+                    .exit(),
+                _ if cli.output.verify_count => TokenCountOptions::Each,
+                token_count => token_count,
+            };
+            let options = GenerateOptions {
+                stdout: cli.output.stdout,
+                token_count,
+                verify_count: cli.output.verify_count,
+                format: cli.format,
+            };
             run::generate(
                 first_path,
                 rest_paths,
-                cli.exclude,
-                cli.no_gitignore,
-                cli.output.stdout,
-                cli.output.token_count,
-                cli.format,
+                discover_options,
+                read_options,
+                options,
             )
             .await
         }
@@ -124,7 +471,15 @@ async fn main() -> Result<()> {
             Ok(())
         }
         Command::Count { top } => {
-            run::count(first_path, rest_paths, cli.exclude, cli.no_gitignore, top).await
+            run::count(first_path, rest_paths, discover_options, read_options, top).await
         }
+        Command::Types => run::types(&discover_options.type_add),
+        Command::Explain { targets } => run::explain(
+            &targets,
+            first_path,
+            rest_paths,
+            discover_options,
+            &read_options,
+        ),
     }
 }
@@ -1,16 +1,25 @@
+use std::collections::BTreeMap;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use arboard::Clipboard;
 use clap::ValueEnum;
 use serde::Serialize;
 use strum::EnumString;
 
-use crate::discovery::discover;
-use crate::files::{Files, ReadStatus};
+use crate::config::Config;
+pub use crate::discovery::DiscoverOptions;
+use crate::discovery::{
+    Classification, Explanation, FileClass, discover, explain as explain_path, type_definitions,
+};
+pub use crate::files::{
+    BinaryDetection, Grep, ImageOptions, LineNumberStyle, LockfileMode, ReadOptions, StripComments,
+    TransformKind, TransformRule,
+};
+use crate::files::{Files, Intake, ReadStatus, Redaction};
 use crate::tokenizer::tokenize;
-use crate::tree::FiletreeNode;
+use crate::tree::{ExcludedEntry, FileCount, FiletreeNode, describe_file_count};
 
 #[derive(Default, Debug, Clone, Copy, EnumString, ValueEnum, Eq, Hash, PartialEq)]
 pub enum TokenCountOptions {
@@ -36,20 +45,160 @@ pub enum Format {
     Yaml,
 }
 
+/// Options controlling where a generated prompt goes and how its tokens are counted.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GenerateOptions {
+    /// Print the prompt to stdout with no summary, rather than copying it to the clipboard.
+    pub stdout: bool,
+    pub token_count: TokenCountOptions,
+    /// With `token_count` of each file, also tokenize the whole prompt to report the drift of the
+    /// derived final count.
+    pub verify_count: bool,
+    pub format: Format,
+}
+
+pub fn types(type_add: &[String]) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    for def in type_definitions(type_add)? {
+        writeln!(handle, "{}: {}", def.name(), def.globs().join(", "))?;
+    }
+    Ok(())
+}
+
+pub fn explain(
+    targets: &[PathBuf],
+    first_path: PathBuf,
+    rest_paths: Vec<PathBuf>,
+    discover_options: DiscoverOptions,
+    read_options: &ReadOptions,
+) -> Result<()> {
+    let mut paths = vec![first_path];
+    paths.extend(rest_paths);
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    for target in targets {
+        let explanation = explain_path(target, &paths, &discover_options);
+        write_explanation(&mut handle, target, explanation, read_options)?;
+    }
+    Ok(())
+}
+
+fn write_explanation(
+    mut writer: impl Write,
+    target: &Path,
+    explanation: Explanation,
+    read_options: &ReadOptions,
+) -> Result<()> {
+    let display = target.display();
+    match explanation {
+        Explanation::NotFound => writeln!(writer, "{display}: does not exist")?,
+        Explanation::Directory => writeln!(
+            writer,
+            "{display}: is a directory, pass the files under it to explain them"
+        )?,
+        Explanation::OutsidePaths => writeln!(
+            writer,
+            "{display}: skipped, not under any of the given paths"
+        )?,
+        Explanation::Symlink => writeln!(writer, "{display}: skipped, symlinks are not followed")?,
+        Explanation::GitDirectory => {
+            writeln!(writer, "{display}: skipped, inside a .git directory")?
+        }
+        Explanation::TypeFilter => writeln!(writer, "{display}: skipped by type filter")?,
+        Explanation::Gitignored(rule) => {
+            writeln!(writer, "{display}: skipped by ignore rule {rule}")?
+        }
+        Explanation::ExcludeGlob(pattern) => {
+            writeln!(writer, "{display}: excluded by --exclude glob `{pattern}`")?
+        }
+        Explanation::Promptignored { rule, overrides } => {
+            writeln!(writer, "{display}: excluded by .promptignore rule {rule}")?;
+            for rule in overrides {
+                writeln!(writer, "  (overriding {rule})")?;
+            }
+        }
+        Explanation::Discovered { whitelisted, class } => {
+            if let Some(pattern) = read_options.denies_sensitive(target) {
+                writeln!(
+                    writer,
+                    "{display}: excluded as sensitive (matches `{pattern}`), allow with --allow-sensitive"
+                )?;
+                return Ok(());
+            }
+            if let Some(pattern) = read_options.skips_test_file(target) {
+                writeln!(
+                    writer,
+                    "{display}: excluded as a test file (matches `{pattern}`) by --no-tests"
+                )?;
+                return Ok(());
+            }
+            if let Some(Classification { class, reason }) = &class
+                && !read_options.includes(*class)
+            {
+                match class {
+                    FileClass::LfsPointer => {
+                        writeln!(writer, "{display}: excluded as a {class} ({reason})")?
+                    }
+                    _ => writeln!(
+                        writer,
+                        "{display}: excluded as {class} ({reason}), include with --include-{class}"
+                    )?,
+                }
+                return Ok(());
+            }
+            // documents and images are taken in as such before binary detection
+            match Intake::of(target, read_options)? {
+                Intake::Extracted(_) => writeln!(
+                    writer,
+                    "{display}: included, text extracted by --extract-documents"
+                )?,
+                Intake::ImageAttached(image) => writeln!(
+                    writer,
+                    "{display}: attached by --images ({}, ~{} tokens)",
+                    image.source.media_type,
+                    image.estimated_tokens()
+                )?,
+                Intake::Sniffed(verdict) => {
+                    let mime = verdict.mime.unwrap_or("no known MIME type");
+                    if verdict.binary && read_options.describe_binaries {
+                        writeln!(writer, "{display}: described, binary detected ({mime})")?;
+                    } else if verdict.binary {
+                        writeln!(writer, "{display}: auto-excluded, binary detected ({mime})")?;
+                    } else {
+                        writeln!(writer, "{display}: included ({mime})")?;
+                    }
+                    let kind = if verdict.binary { "binary" } else { "text" };
+                    writeln!(writer, "  classified as {kind}: {}", verdict.rule)?;
+                }
+            }
+            if let Some(Classification { class, reason }) = class {
+                writeln!(
+                    writer,
+                    "  {class} ({reason}), included with --include-{class}"
+                )?;
+            }
+            if let Some((rule, overrides)) = whitelisted {
+                writeln!(writer, "  whitelisted by .promptignore rule {rule}")?;
+                for rule in overrides {
+                    writeln!(writer, "  (overriding {rule})")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 pub async fn count(
     first_path: PathBuf,
     rest_paths: Vec<PathBuf>,
-    exclude: Vec<glob::Pattern>,
-    include_gitignored: bool,
+    discover_options: DiscoverOptions,
+    read_options: ReadOptions,
     top: Option<u32>,
 ) -> Result<()> {
-    let discovered = discover(
-        first_path.clone(),
-        rest_paths.to_vec(),
-        exclude,
-        include_gitignored,
-    )?;
-    let files = Files::read_from(discovered, true).await?;
+    let read_options = read_options.with_config(Config::load(&first_path)?);
+    let discovered = discover(first_path.clone(), rest_paths.to_vec(), &discover_options)?;
+    let files = Files::read_from(discovered, true, &read_options).await?;
 
     if let Some(count) = top {
         write_top(std::io::stdout(), &files, count)?;
@@ -59,77 +208,177 @@ pub async fn count(
             .map(|r| {
                 let info = r.value();
                 match info.meta.read_status {
-                    ReadStatus::ExcludedExplicitly | ReadStatus::ExcludedBinaryDetected => 0,
-                    ReadStatus::Read => unreachable!(
+                    ReadStatus::ExcludedExplicitly
+                    | ReadStatus::ExcludedDirectory(_)
+                    | ReadStatus::ExcludedBinaryDetected
+                    | ReadStatus::ExcludedSensitive
+                    | ReadStatus::ExcludedTest
+                    | ReadStatus::ExcludedGenerated
+                    | ReadStatus::ExcludedVendored
+                    | ReadStatus::ExcludedMinified
+                    | ReadStatus::ExcludedLfsPointer
+                    | ReadStatus::ExcludedPanicked
+                    | ReadStatus::Duplicate(_) => 0,
+                    ReadStatus::Read | ReadStatus::Described(None) => unreachable!(
                         "non-excluded files should have token count: {}",
                         info.meta.path.display()
                     ),
-                    ReadStatus::TokenCounted(token_count) => token_count,
+                    ReadStatus::TokenCounted(token_count)
+                    | ReadStatus::ImageAttached(token_count)
+                    | ReadStatus::Described(Some(token_count)) => token_count,
                 }
             })
             .sum::<usize>();
         let total_tokens = total_tokens.to_string();
         println!("Total tokens: {total_tokens}");
+        let tokens_saved = files
+            .iter()
+            .filter_map(|r| r.value().meta.tokens_saved)
+            .sum::<usize>();
+        if tokens_saved > 0 {
+            println!("Tokens saved by transforms, license headers and --no-tests: {tokens_saved}");
+        }
+        let tokens_deduplicated = files
+            .iter()
+            .map(|r| r.value().meta.tokens_deduplicated())
+            .sum::<usize>();
+        if tokens_deduplicated > 0 {
+            println!("Tokens saved by deduplication: {tokens_deduplicated}");
+        }
     }
     Ok(())
 }
 
 #[derive(Serialize)]
 struct Output {
+    /// Full text of replaced license headers, with `--license-texts`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    licenses: BTreeMap<String, String>,
     tree: String,
     files: Files,
 }
 
+impl Output {
+    fn to_string(&self, format: Format) -> Result<String> {
+        Ok(match format {
+            Format::Plaintext => unreachable!("plaintext output is written directly"),
+            Format::Json => serde_json::to_string(self)?,
+            Format::Yaml => serde_norway::to_string(self)?,
+        })
+    }
+}
+
 pub async fn generate(
     first_path: PathBuf,
     rest_paths: Vec<PathBuf>,
-    exclude: Vec<glob::Pattern>,
-    no_gitignore: bool,
-    stdout: bool,
-    token_count: TokenCountOptions,
-    format: Format,
+    discover_options: DiscoverOptions,
+    read_options: ReadOptions,
+    options: GenerateOptions,
 ) -> Result<()> {
-    let discovered = discover(
-        first_path.clone(),
-        rest_paths.to_vec(),
-        exclude,
-        no_gitignore,
-    )?;
-    let files =
-        Files::read_from(discovered, matches!(token_count, TokenCountOptions::Each)).await?;
+    let GenerateOptions {
+        stdout,
+        token_count,
+        verify_count,
+        format,
+    } = options;
+    let read_options = read_options.with_config(Config::load(&first_path)?);
+    let discovered = discover(first_path.clone(), rest_paths.to_vec(), &discover_options)?;
+    let files = Files::read_from(
+        discovered,
+        matches!(token_count, TokenCountOptions::Each),
+        &read_options,
+    )
+    .await?;
+
+    let redactions = files.redactions();
+    if read_options.fail_on_secrets && !redactions.is_empty() {
+        let mut message = vec![];
+        write_redactions(&mut message, &redactions)?;
+        bail!(
+            "Refusing to produce a prompt, secrets were detected:\n{}",
+            String::from_utf8_lossy(&message).trim_end()
+        );
+    }
 
     let tree = FiletreeNode::try_from(&files)?;
 
-    let excluded = files.get_excluded();
+    let excluded = tree.excluded();
+    let duplicates = files
+        .iter()
+        .filter(|r| matches!(r.value().meta.read_status, ReadStatus::Duplicate(_)))
+        .map(|r| r.value().meta.tokens_deduplicated())
+        .collect::<Vec<_>>();
 
-    let output = match format {
+    let licenses = if read_options.license_texts {
+        files.license_texts()
+    } else {
+        BTreeMap::new()
+    };
+
+    let count_final = !matches!(token_count, TokenCountOptions::None);
+    // with every file counted, the final count adds their counts to that of the framing around
+    // them, rather than tokenizing the whole output
+    let derive_count = matches!(token_count, TokenCountOptions::Each);
+    let (output, final_token_count, full_token_count) = match format {
         Format::Plaintext => {
-            let mut prompt = vec![];
+            let mut prompt = PromptWriter::new(vec![]);
+            if !licenses.is_empty() {
+                write_licenses(&mut prompt, &licenses)?;
+                write_document_separator(&mut prompt)?;
+            }
             write_filetree(&mut prompt, tree.tty_output()?)?;
             write_document_separator(&mut prompt)?;
             write_files_content(&mut prompt, files)?;
-            String::from_utf8_lossy(&prompt).into_owned()
+            let final_token_count = count_final.then(|| prompt.token_count());
+            let output = String::from_utf8_lossy(&prompt.into_inner()).into_owned();
+            let full_token_count = (derive_count && verify_count).then(|| tokenize(&output).len());
+            (output, final_token_count, full_token_count)
+        }
+        Format::Json | Format::Yaml => {
+            let structured = Output {
+                licenses,
+                tree: tree.tty_output()?,
+                files,
+            };
+            let output = structured.to_string(format)?;
+            let (final_token_count, full_token_count) = if count_final {
+                // base64 image data isn't tokenized as text, so images are counted by estimate
+                let image_tokens = structured.files.strip_image_data();
+                let full_count = || -> Result<usize> {
+                    let counted = if image_tokens > 0 {
+                        tokenize(&structured.to_string(format)?).len()
+                    } else {
+                        tokenize(&output).len()
+                    };
+                    Ok(counted + image_tokens)
+                };
+                if derive_count {
+                    let full_token_count = verify_count.then(full_count).transpose()?;
+                    let text_tokens = structured.files.strip_counted_text(escape_tokens);
+                    let framing_tokens = tokenize(&structured.to_string(format)?).len();
+                    (
+                        Some(framing_tokens + text_tokens + image_tokens),
+                        full_token_count,
+                    )
+                } else {
+                    (Some(full_count()?), None)
+                }
+            } else {
+                (None, None)
+            };
+            (output, final_token_count, full_token_count)
         }
-        Format::Json => serde_json::to_string(&Output {
-            tree: tree.tty_output()?,
-            files,
-        })?,
-        Format::Yaml => serde_norway::to_string(&Output {
-            tree: tree.tty_output()?,
-            files,
-        })?,
-    };
-
-    let final_token_count = match token_count {
-        TokenCountOptions::Final | TokenCountOptions::Each => Some(tokenize(&output).len()),
-        TokenCountOptions::None => None,
     };
+    let drift = final_token_count.zip(full_token_count);
 
     if stdout {
         let stdout = std::io::stdout();
         let mut handle = stdout.lock();
         handle.write_all(output.as_bytes())?;
         handle.flush()?;
+        if let Some((derived, full)) = drift {
+            write_count_drift(std::io::stderr(), derived, full)?;
+        }
         return Ok(()); // no summary if printing prompt to stdout
     }
 
@@ -144,10 +393,94 @@ pub async fn generate(
     if let Some(token_count) = final_token_count {
         println!("{token_count} total tokens copied ({format})");
     }
+    if let Some((derived, full)) = drift {
+        write_count_drift(std::io::stdout(), derived, full)?;
+    }
+    if !duplicates.is_empty() {
+        write_duplicate_summary(std::io::stdout(), &duplicates)?;
+    }
     if !excluded.is_empty() {
-        println!("Excluded {} files: {:?}", excluded.len(), excluded);
+        write_excluded_summary(std::io::stdout(), &excluded)?;
+    }
+    if !redactions.is_empty() {
+        write_redaction_summary(std::io::stdout(), &redactions)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a prompt, keeping apart the framing around file contents, like headers and
+/// separators, from contents whose tokens were already counted. The prompt's token count is then
+/// that of the framing plus the counted contents.
+struct PromptWriter<W> {
+    writer: W,
+    framing: String,
+    counted_tokens: usize,
+}
+
+impl<W: Write> PromptWriter<W> {
+    const fn new(writer: W) -> Self {
+        Self {
+            writer,
+            framing: String::new(),
+            counted_tokens: 0,
+        }
+    }
+
+    /// Writes file content, as framing if its tokens weren't counted.
+    fn write_content(&mut self, content: &str, token_count: Option<usize>) -> Result<()> {
+        match token_count {
+            Some(token_count) => {
+                self.writer.write_all(content.as_bytes())?;
+                self.counted_tokens += token_count;
+            }
+            None => self.write_all(content.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    fn token_count(&self) -> usize {
+        tokenize(&self.framing).len() + self.counted_tokens
+    }
+
+    fn into_inner(self) -> W {
+        self.writer
     }
+}
+
+impl<W: Write> Write for PromptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.framing
+            .push_str(&String::from_utf8_lossy(&buf[..written]));
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Estimates the tokens that escaping text in a JSON or YAML string adds, as the escapes of
+/// newlines, quotes and backslashes mostly take a token each.
+fn escape_tokens(text: &str) -> usize {
+    text.chars()
+        .filter(|c| matches!(c, '"' | '\\') || c.is_control())
+        .count()
+}
 
+/// Reports how far the derived token count is from a full count of the output.
+fn write_count_drift(mut writer: impl Write, derived: usize, full: usize) -> Result<()> {
+    let drift = derived as i64 - full as i64;
+    let percent = if full == 0 {
+        0.0
+    } else {
+        drift as f64 / full as f64 * 100.0
+    };
+    writeln!(
+        writer,
+        "Verified against a full count of {full} tokens, drift {drift:+} ({percent:+.2}%)"
+    )?;
     Ok(())
 }
 
@@ -158,13 +491,67 @@ fn write_filetree(mut writer: impl Write, tree: String) -> Result<()> {
     Ok(())
 }
 
+fn write_licenses(mut writer: impl Write, licenses: &BTreeMap<String, String>) -> Result<()> {
+    writeln!(writer, "Licenses:")?;
+    for (license, text) in licenses {
+        writeln!(writer)?;
+        writeln!(writer, "[license header: {license}]")?;
+        writeln!(writer, "{text}")?;
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn write_excluded_summary(mut writer: impl Write, excluded: &[ExcludedEntry]) -> Result<()> {
+    let total = excluded
+        .iter()
+        .map(ExcludedEntry::file_count)
+        .sum::<FileCount>();
+    writeln!(writer, "Excluded {total}:")?;
+    for entry in excluded {
+        writeln!(writer, "  {entry}")?;
+    }
+    Ok(())
+}
+
+/// Summarizes files left out as duplicates, given the tokens each would have taken.
+fn write_duplicate_summary(mut writer: impl Write, duplicates: &[usize]) -> Result<()> {
+    let files = describe_file_count(duplicates.len());
+    match duplicates.iter().sum::<usize>() {
+        0 => writeln!(writer, "Deduplicated {files}")?,
+        saved => writeln!(writer, "Deduplicated {files}, saving {saved} tokens")?,
+    }
+    Ok(())
+}
+
+fn write_redaction_summary(
+    mut writer: impl Write,
+    redactions: &[(PathBuf, Redaction)],
+) -> Result<()> {
+    match redactions.len() {
+        1 => writeln!(writer, "Redacted 1 secret:")?,
+        count => writeln!(writer, "Redacted {count} secrets:")?,
+    }
+    write_redactions(writer, redactions)
+}
+
+fn write_redactions(mut writer: impl Write, redactions: &[(PathBuf, Redaction)]) -> Result<()> {
+    for (path, redaction) in redactions {
+        match redaction.line {
+            Some(line) => writeln!(writer, "  {}:{line}: {}", path.display(), redaction.kind)?,
+            None => writeln!(writer, "  {}: {}", path.display(), redaction.kind)?,
+        }
+    }
+    Ok(())
+}
+
 fn write_document_separator(mut writer: impl Write) -> Result<()> {
     writeln!(writer, "---")?;
     writeln!(writer)?;
     Ok(())
 }
 
-fn write_files_content(mut writer: impl Write, files: Files) -> Result<()> {
+fn write_files_content<W: Write>(writer: &mut PromptWriter<W>, files: Files) -> Result<()> {
     let mut paths = files.iter().map(|r| r.key().clone()).collect::<Vec<_>>();
     paths.sort();
     for path in paths.iter() {
@@ -174,12 +561,26 @@ fn write_files_content(mut writer: impl Write, files: Files) -> Result<()> {
         }
         writeln!(writer, "{}:", path.display())?;
         writeln!(writer)?;
-        writeln!(
-            writer,
-            "{}",
-            info.utf8
-                .expect("should be able to get utf8 if this file wasn't excluded")
-        )?;
+        if let ReadStatus::Duplicate(duplicate) = &info.meta.read_status {
+            writeln!(writer, "({duplicate})")?;
+            writeln!(writer, "---")?;
+            continue;
+        }
+        if let Some(image) = info.image {
+            // plaintext can't carry images, they are attached in the structured formats
+            writeln!(
+                writer,
+                "[image: {}, {}x{}, attached in json and yaml output]",
+                image.source.media_type, image.width, image.height
+            )?;
+            writeln!(writer, "---")?;
+            continue;
+        }
+        let content = info
+            .utf8
+            .expect("should be able to get utf8 if this file wasn't excluded");
+        writer.write_content(&content, info.meta.text_token_count())?;
+        writeln!(writer)?;
         writeln!(writer, "---")?;
     }
 
@@ -193,7 +594,11 @@ fn write_top(mut writer: impl Write, files: &Files, top: u32) -> Result<()> {
         .filter(|entry| !entry.value().meta.is_excluded())
         .collect::<Vec<_>>();
 
-    let skipped_files = files.len().saturating_sub(entries.len());
+    let skipped = files
+        .iter()
+        .filter(|entry| entry.value().meta.is_excluded())
+        .map(|entry| entry.value().meta.file_count())
+        .sum::<FileCount>();
     let all_file_count = entries.len();
 
     entries.sort_by(|a, b| {
@@ -209,8 +614,25 @@ fn write_top(mut writer: impl Write, files: &Files, top: u32) -> Result<()> {
 
     for entry in entries.iter().take(top as usize) {
         let path = entry.key();
-        let token_count = entry.value().meta.token_count_or_zero();
-        writeln!(writer, "{}: {} tokens", path.display(), token_count)?;
+        let meta = &entry.value().meta;
+        let token_count = meta.token_count_or_zero();
+        let saved = meta
+            .tokens_saved
+            .map(|saved| format!(" ({saved} saved)"))
+            .unwrap_or_default();
+        let converted = meta
+            .converted
+            .and_then(|converted| {
+                let full_tokens = converted.full_tokens?;
+                Some(format!(" ({} from {full_tokens})", converted.form))
+            })
+            .unwrap_or_default();
+        writeln!(
+            writer,
+            "{}: {} tokens{converted}{saved}",
+            path.display(),
+            token_count
+        )?;
         top_total_tokens += token_count;
         all_total_tokens += token_count;
         top_file_count += 1;
@@ -230,11 +652,8 @@ fn write_top(mut writer: impl Write, files: &Files, top: u32) -> Result<()> {
         writer,
         "All {all_file_count} files = {all_total_tokens} tokens"
     )?;
-    if skipped_files > 0 {
-        writeln!(
-            writer,
-            "{skipped_files} files skipped (excluded or binary detected)"
-        )?;
+    if skipped != FileCount::default() {
+        writeln!(writer, "{skipped} skipped (excluded or binary detected)")?;
     }
 
     Ok(())
@@ -243,35 +662,56 @@ fn write_top(mut writer: impl Write, files: &Files, top: u32) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use std::fs;
-    use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
 
     use anyhow::Result;
 
     use super::*;
     use crate::discovery::DiscoveredFile;
+    use crate::test_util::TempDir;
     use crate::tree::FiletreeNode;
 
-    struct TempDir {
-        path: PathBuf,
-    }
+    #[test]
+    fn explain_takes_in_documents_and_images_before_binary_detection() -> Result<()> {
+        use std::io::{Cursor, Write};
 
-    impl TempDir {
-        fn new() -> Self {
-            let unique = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("system time before unix epoch")
-                .as_nanos();
-            let path = std::env::temp_dir().join(format!("prompt-run-test-{unique}"));
-            fs::create_dir_all(&path).expect("should create temp dir");
-            Self { path }
-        }
-    }
+        let temp = TempDir::new();
+        let mut docx = zip::ZipWriter::new(Cursor::new(vec![]));
+        docx.start_file(
+            "word/document.xml",
+            zip::write::SimpleFileOptions::default(),
+        )?;
+        docx.write_all(
+            br#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Design</w:t></w:r></w:p></w:body></w:document>"#,
+        )?;
+        let report = temp.path.join("report.docx");
+        fs::write(&report, docx.finish()?.into_inner())?;
+        let logo = temp.path.join("logo.png");
+        image::RgbImage::new(30, 25).save(&logo)?;
+
+        let explain = |target: &Path, read_options: &ReadOptions| -> Result<String> {
+            let mut output = vec![];
+            let explanation = Explanation::Discovered {
+                whitelisted: None,
+                class: None,
+            };
+            write_explanation(&mut output, target, explanation, read_options)?;
+            Ok(String::from_utf8(output)?)
+        };
+        let plain = ReadOptions::default();
+        assert!(explain(&report, &plain)?.contains("auto-excluded, binary detected"));
+        assert!(explain(&logo, &plain)?.contains("auto-excluded, binary detected (image/png)"));
+
+        let taking_in = ReadOptions {
+            extract_documents: true,
+            images: Some(ImageOptions::default()),
+            ..ReadOptions::default()
+        };
+        assert!(explain(&report, &taking_in)?.contains("text extracted by --extract-documents"));
+        assert!(
+            explain(&logo, &taking_in)?.contains("attached by --images (image/png, ~1 tokens)")
+        );
 
-    impl Drop for TempDir {
-        fn drop(&mut self) {
-            let _ = fs::remove_dir_all(&self.path);
-        }
+        Ok(())
     }
 
     #[tokio::test]
@@ -284,14 +724,18 @@ mod tests {
             DiscoveredFile {
                 path: included_path.clone(),
                 excluded: false,
+                pruned: None,
+                class: None,
             },
             DiscoveredFile {
                 path: temp.path.join("target/excluded.bin"),
                 excluded: true,
+                pruned: None,
+                class: None,
             },
         ];
 
-        let files = Files::read_from(discovered, true).await?;
+        let files = Files::read_from(discovered, true, &ReadOptions::default()).await?;
 
         let mut buffer = Vec::new();
         write_top(&mut buffer, &files, 5)?;
@@ -301,7 +745,7 @@ mod tests {
         assert!(!output.contains("excluded.bin"));
         assert!(output.contains("Top 1 files ="));
         assert!(output.contains("All 1 files ="));
-        assert!(output.contains("1 files skipped"));
+        assert!(output.contains("1 file skipped"));
 
         Ok(())
     }
@@ -315,17 +759,19 @@ mod tests {
         let discovered = vec![DiscoveredFile {
             path: included_path.clone(),
             excluded: false,
+            pruned: None,
+            class: None,
         }];
 
-        let files = Files::read_from(discovered, false).await?;
+        let files = Files::read_from(discovered, false, &ReadOptions::default()).await?;
         let tree = FiletreeNode::try_from(&files)?;
 
-        let mut buffer = Vec::new();
-        write_filetree(&mut buffer, tree.tty_output()?)?;
-        write_document_separator(&mut buffer)?;
-        write_files_content(&mut buffer, files)?;
+        let mut prompt = PromptWriter::new(Vec::new());
+        write_filetree(&mut prompt, tree.tty_output()?)?;
+        write_document_separator(&mut prompt)?;
+        write_files_content(&mut prompt, files)?;
 
-        let output = String::from_utf8(buffer).expect("valid utf8 output");
+        let output = String::from_utf8(prompt.into_inner()).expect("valid utf8 output");
         let doc_sep_idx = output.find("---\n\n").expect("document separator present");
         let first_file_idx = output
             .find(&format!("{}:", included_path.display()))
@@ -335,4 +781,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn prompt_writer_counts_framing_apart_from_counted_content() -> Result<()> {
+        let mut prompt = PromptWriter::new(Vec::new());
+        writeln!(prompt, "main.rs:")?;
+        prompt.write_content("fn main() {}", Some(100))?;
+        prompt.write_content("\nuncounted", None)?;
+
+        assert_eq!(
+            prompt.token_count(),
+            tokenize("main.rs:\n\nuncounted").len() + 100
+        );
+        assert_eq!(prompt.into_inner(), b"main.rs:\nfn main() {}\nuncounted");
+
+        Ok(())
+    }
 }
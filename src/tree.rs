@@ -1,16 +1,20 @@
 use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use ptree::TreeItem;
 use ptree::print_config::StyleWhen;
+use serde::{Deserialize, Serialize};
 
-use crate::files::{FileMeta, Files, strip_dot_prefix};
+use crate::files::{Converted, FileMeta, Files, ReadStatus, strip_dot_prefix};
 
 #[derive(Debug, Clone)]
 pub struct FiletreeNode {
     name: String,
     meta: Option<FileMeta>,
     children: BTreeMap<String, Self>,
+    /// Number of files under this directory, if they were all excluded and collapsed.
+    collapsed: Option<FileCount>,
 }
 
 impl FiletreeNode {
@@ -19,6 +23,7 @@ impl FiletreeNode {
             name: name.to_string(),
             children: BTreeMap::new(),
             meta,
+            collapsed: None,
         }
     }
 
@@ -57,6 +62,153 @@ impl FiletreeNode {
             entry.insert_path(&components[1..], meta);
         }
     }
+
+    /// Collapses directories whose files were all explicitly excluded into a single node,
+    /// returning the number of files under this node if it is fully excluded.
+    fn collapse_excluded(&mut self) -> Option<FileCount> {
+        if let Some(meta) = &self.meta {
+            return match meta.read_status {
+                ReadStatus::ExcludedExplicitly => Some(FileCount::one()),
+                ReadStatus::ExcludedDirectory(files) => {
+                    // already pruned as a whole during discovery
+                    self.meta = None;
+                    self.collapsed = Some(files);
+                    self.collapsed
+                }
+                _ => None,
+            };
+        }
+        let mut total = FileCount::default();
+        let mut fully_excluded = true;
+        for child in self.children.values_mut() {
+            match child.collapse_excluded() {
+                Some(count) => total += count,
+                None => fully_excluded = false,
+            }
+        }
+        if !fully_excluded || self.children.is_empty() {
+            return None;
+        }
+        self.children.clear();
+        self.collapsed = Some(total);
+        self.collapsed
+    }
+
+    /// Returns excluded paths, with fully excluded directories aggregated into a single entry.
+    pub fn excluded(&self) -> Vec<ExcludedEntry> {
+        let mut excluded = vec![];
+        for child in self.children.values() {
+            child.collect_excluded(Path::new(""), &mut excluded);
+        }
+        excluded
+    }
+
+    fn collect_excluded(&self, parent: &Path, excluded: &mut Vec<ExcludedEntry>) {
+        let path = parent.join(&self.name);
+        if let Some(files) = self.collapsed {
+            excluded.push(ExcludedEntry::Directory { path, files });
+            return;
+        }
+        if let Some(meta) = &self.meta {
+            if meta.is_excluded() {
+                excluded.push(ExcludedEntry::File(path));
+            }
+            return;
+        }
+        for child in self.children.values() {
+            child.collect_excluded(&path, excluded);
+        }
+    }
+}
+
+/// An excluded file, or a fully excluded directory collapsed into a single entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExcludedEntry {
+    File(PathBuf),
+    Directory { path: PathBuf, files: FileCount },
+}
+
+impl ExcludedEntry {
+    pub const fn file_count(&self) -> FileCount {
+        match self {
+            Self::File(_) => FileCount::one(),
+            Self::Directory { files, .. } => *files,
+        }
+    }
+}
+
+impl std::fmt::Display for ExcludedEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Directory { path, files } => write!(f, "{}/ ({files})", path.display()),
+        }
+    }
+}
+
+/// A number of files, which is only a lower bound if counting stopped early in a huge pruned
+/// directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileCount {
+    pub files: usize,
+    /// Whether there are more files than were counted.
+    pub capped: bool,
+}
+
+impl FileCount {
+    pub const fn one() -> Self {
+        Self {
+            files: 1,
+            capped: false,
+        }
+    }
+}
+
+impl std::ops::AddAssign for FileCount {
+    fn add_assign(&mut self, other: Self) {
+        self.files += other.files;
+        self.capped |= other.capped;
+    }
+}
+
+impl std::iter::Sum for FileCount {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, count| {
+            total += count;
+            total
+        })
+    }
+}
+
+impl std::fmt::Display for FileCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.capped {
+            write!(f, "{}+ files", format_count(self.files))
+        } else {
+            write!(f, "{}", describe_file_count(self.files))
+        }
+    }
+}
+
+/// Formats a count with thousands separators, e.g. `4,211`.
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Describes a number of files, e.g. `1 file` or `4,211 files`.
+pub fn describe_file_count(count: usize) -> String {
+    match count {
+        1 => "1 file".to_string(),
+        count => format!("{} files", format_count(count)),
+    }
 }
 
 impl TreeItem for FiletreeNode {
@@ -69,23 +221,74 @@ impl TreeItem for FiletreeNode {
     ) -> std::io::Result<()> {
         match &self.meta {
             Some(meta) => {
+                // files read despite their class are marked with it
+                let name = match meta.class {
+                    Some(class) if !meta.is_excluded() => format!("{} [{class}]", self.name),
+                    _ => self.name.clone(),
+                };
                 let text = match meta.read_status {
-                    crate::files::ReadStatus::ExcludedExplicitly => {
-                        format!("{} (excluded)", &self.name)
+                    ReadStatus::ExcludedExplicitly => {
+                        format!("{} (excluded)", &name)
+                    }
+                    ReadStatus::ExcludedDirectory(files) => {
+                        format!("{}/ (excluded: {files})", &name)
+                    }
+                    ReadStatus::ExcludedBinaryDetected => {
+                        format!("{} (auto-excluded, binary detected)", &name)
+                    }
+                    ReadStatus::ExcludedSensitive => {
+                        format!("{} (excluded, sensitive)", &name)
+                    }
+                    ReadStatus::ExcludedTest => format!("{} (excluded, test)", &name),
+                    ReadStatus::ExcludedGenerated => format!("{} (excluded, generated)", &name),
+                    ReadStatus::ExcludedVendored => format!("{} (excluded, vendored)", &name),
+                    ReadStatus::ExcludedMinified => format!("{} (excluded, minified)", &name),
+                    ReadStatus::ExcludedLfsPointer => {
+                        format!("{} (excluded, Git LFS pointer)", &name)
+                    }
+                    ReadStatus::ExcludedPanicked => format!("{} (excluded, failed to read)", &name),
+                    ReadStatus::ImageAttached(estimated_tokens) => {
+                        format!("{} (image attached, ~{estimated_tokens} tokens)", &name)
                     }
-                    crate::files::ReadStatus::ExcludedBinaryDetected => {
-                        format!("{} (auto-excluded, binary detected)", &self.name)
+                    ReadStatus::Described(Some(token_count)) => {
+                        format!("{} (described: {token_count} tokens)", &name)
                     }
-                    crate::files::ReadStatus::Read => self.name.to_owned(),
-                    crate::files::ReadStatus::TokenCounted(token_count) => {
-                        format!("{} ({} tokens)", &self.name, token_count)
+                    ReadStatus::Described(None) => format!("{} (described)", &name),
+                    ReadStatus::Duplicate(ref duplicate) => format!("{} ({duplicate})", &name),
+                    ReadStatus::Read if let Some(converted) = meta.converted => {
+                        format!("{} ({})", &name, converted.form)
+                    }
+                    ReadStatus::Read => name,
+                    ReadStatus::TokenCounted(token_count) => {
+                        let saved = meta
+                            .tokens_saved
+                            .map(|saved| format!(", {saved} saved"))
+                            .unwrap_or_default();
+                        match meta.converted {
+                            Some(Converted {
+                                form,
+                                full_tokens: Some(full_tokens),
+                            }) => format!(
+                                "{} ({form}: {token_count} of {full_tokens} tokens{saved})",
+                                &name
+                            ),
+                            Some(Converted {
+                                form,
+                                full_tokens: None,
+                            }) => format!("{} ({form}: {token_count} tokens{saved})", &name),
+                            None => format!("{} ({token_count} tokens{saved})", &name),
+                        }
                     }
                 };
                 write!(f, "{}", style.paint(text))
             }
-            None => {
-                write!(f, "{}", style.paint(&self.name))
-            }
+            None => match self.collapsed {
+                Some(count) => {
+                    let text = format!("{}/ (excluded: {count})", &self.name);
+                    write!(f, "{}", style.paint(text))
+                }
+                None => write!(f, "{}", style.paint(&self.name)),
+            },
         }
     }
 
@@ -121,6 +324,98 @@ impl TryFrom<&Files> for FiletreeNode {
 
             root.insert_path(&components, Some(meta));
         }
+        for child in root.children.values_mut() {
+            child.collapse_excluded();
+        }
         Ok(root)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, read_status: ReadStatus) -> (Vec<&str>, FileMeta) {
+        let meta = FileMeta::new(PathBuf::from(path), read_status);
+        (path.split('/').collect(), meta)
+    }
+
+    #[test]
+    fn fully_excluded_directories_are_collapsed() -> Result<()> {
+        let mut root = FiletreeNode::new(".", None);
+        for (components, meta) in [
+            file("src/main.rs", ReadStatus::Read),
+            file("src/secret.txt", ReadStatus::ExcludedExplicitly),
+            file("target/debug/a", ReadStatus::ExcludedExplicitly),
+            file("target/debug/b", ReadStatus::ExcludedExplicitly),
+            file("target/c", ReadStatus::ExcludedExplicitly),
+            file(
+                "target/release",
+                ReadStatus::ExcludedDirectory(FileCount {
+                    files: 4211,
+                    capped: false,
+                }),
+            ),
+            file(
+                "node_modules",
+                ReadStatus::ExcludedDirectory(FileCount {
+                    files: 10_000,
+                    capped: true,
+                }),
+            ),
+        ] {
+            root.insert_path(&components, Some(meta));
+        }
+        for child in root.children.values_mut() {
+            child.collapse_excluded();
+        }
+
+        let output = root.ptree(&ptree::PrintConfig {
+            styled: StyleWhen::Never,
+            ..ptree::PrintConfig::default()
+        })?;
+        assert!(output.contains("target/ (excluded: 4,214 files)"));
+        assert!(output.contains("node_modules/ (excluded: 10,000+ files)"));
+        assert!(!output.contains("debug"));
+        assert!(output.contains("secret.txt (excluded)"));
+        let excluded = root.excluded();
+        assert_eq!(
+            excluded,
+            vec![
+                ExcludedEntry::Directory {
+                    path: PathBuf::from("node_modules"),
+                    files: FileCount {
+                        files: 10_000,
+                        capped: true,
+                    }
+                },
+                ExcludedEntry::File(PathBuf::from("src/secret.txt")),
+                ExcludedEntry::Directory {
+                    path: PathBuf::from("target"),
+                    files: FileCount {
+                        files: 4214,
+                        capped: false,
+                    }
+                }
+            ]
+        );
+        assert_eq!(
+            excluded
+                .iter()
+                .map(ExcludedEntry::file_count)
+                .sum::<FileCount>()
+                .to_string(),
+            "14,215+ files"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn format_count_adds_thousands_separators() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(4211), "4,211");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+}